@@ -0,0 +1,58 @@
+// Feeds random bytes as aff and dic files to the parser and checks the words with the result.
+// The input is split at the first zero byte: the aff file before it, the dic file after it.
+// Without the zero byte, the whole input is the aff file, e.g. in the neadic format.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use neaspell_core::core_speller::Spell;
+use neaspell_core::text_parser::{LineReader, TextParser};
+
+/// Reads the lines of the fuzzed bytes, with the line ends.
+struct FuzzLineReader {
+    flr_extension: String,
+    flr_lines: Vec<Vec<u8>>,
+    flr_next_ix: usize,
+}
+
+impl FuzzLineReader {
+    fn new(flr_extension: &str, data: &[u8]) -> FuzzLineReader {
+        FuzzLineReader {
+            flr_extension: flr_extension.to_string(),
+            flr_lines: data.split_inclusive(|b| *b == b'\n').map(|line| line.to_vec()).collect(),
+            flr_next_ix: 0,
+        }
+    }
+}
+
+impl LineReader for FuzzLineReader {
+    fn get_base_name(&self) -> String {
+        String::from("fuzz")
+    }
+    fn get_extension(&self) -> String {
+        self.flr_extension.clone()
+    }
+    fn read_line(&mut self) -> Option<Vec<u8>> {
+        let line = self.flr_lines.get(self.flr_next_ix)?.clone();
+        self.flr_next_ix += 1;
+        Some(line)
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let (aff, dic) = match data.iter().position(|b| *b == 0) {
+        Some(zero_pos) => (&data[..zero_pos], &data[zero_pos + 1..]),
+        None => (data, &data[data.len()..]),
+    };
+    let mut text_parser = TextParser::new();
+    text_parser.tps_showing_details = true;
+    text_parser.tps_validating = true;
+    let spell_lang = text_parser.parse_lang_readers(
+        FuzzLineReader::new(TextParser::EXT_AFF, aff),
+        FuzzLineReader::new(TextParser::EXT_DIC, dic),
+    );
+    // the words of the input exercise the affixes and the compounds
+    for word in String::from_utf8_lossy(data).split_whitespace().take(20) {
+        let _ = Spell::check_token(&spell_lang, word);
+    }
+    let _ = spell_lang.summary().to_string();
+});
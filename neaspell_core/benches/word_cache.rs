@@ -0,0 +1,90 @@
+// Compares Spell::check_token with WordCache::check_token on a repetitive text.
+// Run with: cargo bench -p neaspell_core --bench word_cache
+use std::time::{Duration, Instant};
+
+use neaspell_core::core_speller::{Spell, SpellLang, WordCache};
+use neaspell_core::text_parser::{LineReader, TextParser};
+
+/// Reads the lines of a generated aff or dic text.
+struct TextLineReader {
+    tlr_extension: &'static str,
+    tlr_lines: Vec<Vec<u8>>,
+    tlr_next_ix: usize,
+}
+
+impl TextLineReader {
+    fn new(tlr_extension: &'static str, text: &str) -> TextLineReader {
+        TextLineReader {
+            tlr_extension,
+            tlr_lines: text.split_inclusive('\n').map(|line| line.as_bytes().to_vec()).collect(),
+            tlr_next_ix: 0,
+        }
+    }
+}
+
+impl LineReader for TextLineReader {
+    fn get_base_name(&self) -> String {
+        String::from("bench")
+    }
+    fn get_extension(&self) -> String {
+        self.tlr_extension.to_string()
+    }
+    fn read_line(&mut self) -> Option<Vec<u8>> {
+        let line = self.tlr_lines.get(self.tlr_next_ix)?.clone();
+        self.tlr_next_ix += 1;
+        Some(line)
+    }
+}
+
+/// The stems made of syllables, e.g. "kalomeru", with suffixes and compounds.
+fn bench_lang() -> SpellLang {
+    let aff = "COMPOUNDFLAG Y\nCOMPOUNDMIN 3\nSFX S Y 2\nSFX S 0 s [^s]\nSFX S 0 es s\n\
+        SFX D Y 2\nSFX D 0 ed [^e]\nSFX D 0 d e\nSFX G Y 2\nSFX G e ing e\nSFX G 0 ing [^e]\n";
+    let syllables = ["ka", "lo", "me", "ru", "ti", "sa", "ne", "po", "di", "fu"];
+    let mut stems: Vec<String> = vec![];
+    for a in syllables {
+        for b in syllables {
+            for c in syllables {
+                stems.push(format!("{a}{b}{c}/SDGY"));
+                stems.push(format!("{a}{b}{c}e/SDG"));
+            }
+        }
+    }
+    let dic = format!("{}\n{}\n", stems.len(), stems.join("\n"));
+    let mut text_parser = TextParser::new();
+    text_parser.parse_lang_readers(TextLineReader::new(TextParser::EXT_AFF, aff), TextLineReader::new(TextParser::EXT_DIC, &dic))
+}
+
+/// A text of 100 distinct words, half of them misspelled, repeated 'repeats' times.
+fn bench_words(repeats: usize) -> Vec<String> {
+    let paragraph: Vec<String> = (0..100)
+        .map(|ix| {
+            let stem = ["kalome", "rutisa", "nepodi", "fukalo", "merutisa"][ix % 5];
+            match ix % 4 {
+                0 => format!("{stem}s"),
+                1 => format!("{stem}ing"),
+                2 => format!("{stem}xqz"),
+                _ => format!("{stem}{}qq", ix),
+            }
+        })
+        .collect();
+    (0..repeats).flat_map(|_| paragraph.iter().cloned()).collect()
+}
+
+fn measure(name: &str, words: &[String], mut check: impl FnMut(&str) -> bool) -> (Duration, usize) {
+    let started = Instant::now();
+    let accepted = words.iter().filter(|word| check(word)).count();
+    let elapsed = started.elapsed();
+    println!("{name:>12}: {} words in {elapsed:?}, {accepted} accepted", words.len());
+    (elapsed, accepted)
+}
+
+fn main() {
+    let spell_lang = bench_lang();
+    let words = bench_words(500);
+    let (uncached, uncached_accepted) = measure("check_token", &words, |word| Spell::check_token(&spell_lang, word));
+    let mut word_cache = WordCache::new(10_000);
+    let (cached, cached_accepted) = measure("WordCache", &words, |word| word_cache.check_token(&spell_lang, word));
+    assert_eq!(uncached_accepted, cached_accepted);
+    println!("{:>12}: {:.1}x", "speedup", uncached.as_secs_f64() / cached.as_secs_f64());
+}
@@ -1,11 +1,29 @@
 /// UTF-8 engine for spell checking.
 //use std::collections::HashMap;
+#[cfg(not(feature = "fxhash"))]
 pub use hashbrown::{HashMap,HashSet};
+/// With the feature "fxhash", the hash function doesn't require random
+/// numbers (as in webassembly) and the iteration order is the same in each run.
+#[cfg(feature = "fxhash")]
+pub type HashMap<K, V> = hashbrown::HashMap<K, V, rustc_hash::FxBuildHasher>;
+#[cfg(feature = "fxhash")]
+pub type HashSet<K> = hashbrown::HashSet<K, rustc_hash::FxBuildHasher>;
+use std::cmp::Reverse;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+#[cfg(feature = "nfc")]
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+use crate::text_parser::{LineReader, Parser, TextParser};
 
 pub enum ModeFlag {
     /// compatible processing, to have external test parity
     /// right now,
     TestCompat = 1,
+    /// underscore is a word character, e.g. in programming identifiers like parse_html
+    Identifiers = 2,
+    /// curly quotes and apostrophes are checked as the ascii ones, e.g. don’t as don't
+    NormalizeQuotes = 4,
     //LowercasePreInternet = 1, or LowercaseInternet, www.england.uk, @unesco, perhaps with tokenizer, too
     // after other flags are defined, the option --compat will select TestCompat
     // that will include several flags
@@ -14,10 +32,49 @@ pub enum ModeFlag {
     // parse programming identifiers: ParseHtml, parseHtml, parse_html
 }
 
+/// The check and suggestion features that can be disabled, e.g. for speed or debugging,
+/// combined in slg_features. By default, all of them are enabled.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum CheckFeature {
+    /// the words composed of dictionary words, by COMPOUNDFLAG and COMPOUNDRULE
+    Compound = 1,
+    /// the second prefix or suffix, allowed by the continuation flags of the first one
+    AffixContinuation = 2,
+    /// all the suggestions for the misspelled words
+    Suggest = 4,
+    /// the suggestions of the words sounding similar, by the PHONE table
+    Phonetic = 8,
+    /// the suggestions of the words sharing most n-grams
+    Ngram = 16,
+    /// the words split from the soft prefixes and suffixes of NEA SOFTPREFIX and SOFTSUFFIX
+    /// when tokenizing, e.g. "enabled" in "USB-enabled"
+    SoftSplit = 32,
+}
+
+impl CheckFeature {
+    /// All the features combined.
+    pub const ALL: u32 = 63;
+
+    /// Returns the feature by its name in option --features, e.g. "compound".
+    pub fn from_name(name: &str) -> Option<CheckFeature> {
+        match name {
+            "compound" => Some(CheckFeature::Compound),
+            "affix-continuation" => Some(CheckFeature::AffixContinuation),
+            "suggest" => Some(CheckFeature::Suggest),
+            "phonetic" => Some(CheckFeature::Phonetic),
+            "ngram" => Some(CheckFeature::Ngram),
+            "soft-split" => Some(CheckFeature::SoftSplit),
+            _ => None,
+        }
+    }
+}
+
 /// Parsed value of FLAG tag, and the default value when no FLAG.
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum FlagFormat {
-    /// default, one character 
+    /// default without FLAG tag, one character.
+    /// The flags are split after decoding by SET, so a multi-byte character
+    /// is one flag, as with FLAG UTF-8.
     SingleChar,
     /// "FLAG UTF-8", one unicode character
     SingleUni,
@@ -31,9 +88,21 @@ pub enum FlagFormat {
     Numeric,
 }
 
+impl FlagFormat {
+    /// Returns the format for the value of FLAG tag: "UTF-8", "long" or "num".
+    pub fn from_tag_value(flag_value: &str) -> Option<FlagFormat> {
+        match flag_value {
+            "UTF-8" => Some(FlagFormat::SingleUni),
+            "long" => Some(FlagFormat::DoubleChar),
+            "num" => Some(FlagFormat::Numeric),
+            _ => None,
+        }
+    }
+}
+
 /// Each word in the dictionary can have one or more flags.
 /// Flags can be defined with many elements.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum FlagType {
     FlagAffix,
     FlagAf,
@@ -61,10 +130,14 @@ pub enum FlagType {
 /// pairs: tag name and associated flag type
 pub type FlagNameAndType = (&'static str, FlagType);
 
+/// One flag of COMPOUNDRULE and its quantifier: '*' (zero or more), '?' (zero or one)
+/// or ' ' (exactly one).
+pub type CompoundRuleItem = (String, char);
+
 /// Whan language script has lowercase and uppercase characters,
 /// dictionary normalizes uppercase and initial-uppercase words to lowercase.
 /// Here are all the casing possiblilites.
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Debug)]
 pub enum CharCase {
     Lower,   // all characters are lowercase
     Initial, // the first character is uppercase, the remaining are lowercase
@@ -120,8 +193,16 @@ impl CharCase {
         }
     }
 
-    #[allow(dead_code)]
-    fn restore_case(char_case: CharCase, word: &str) -> String {
+    /// Splits the word into its case and the dictionary key, same as normalize_case.
+    /// The original word is restored with restore_case:
+    /// CharCase::restore_case(char_case, &key) == word
+    pub fn decompose(word: &str) -> (CharCase, String) {
+        CharCase::normalize_case(word)
+    }
+
+    /// Returns the word with the character case restored, the opposite of decompose.
+    /// The 'word' is the dictionary key. With CharCase::Other, the key is already the word.
+    pub fn restore_case(char_case: CharCase, word: &str) -> String {
         // web, Hague, UNICEF, 's-Gravenhage, 中国
         if char_case == CharCase::Lower {
             return word.to_string();
@@ -169,6 +250,9 @@ pub struct WordFlag {
 
 impl Regex {
     pub fn new(rgx_def: String) -> Regex {
+        // with feature nfc, a composed letter matches its decomposed form, too
+        #[cfg(feature = "nfc")]
+        let rgx_def: String = rgx_def.nfc().collect();
         // rgx_vec[i].1 is true if the characters
         // in rgx_vec[i].0 are accepted (included)
         let mut rgx_vec: Vec<(String, bool)> = vec![];
@@ -238,6 +322,15 @@ impl Regex {
         if let Some(_) = self.rgx_error {
             return false;
         }
+        #[cfg(feature = "nfc")]
+        let normalized: String;
+        #[cfg(feature = "nfc")]
+        let s = if is_nfc_quick(s.chars()) == IsNormalized::Yes {
+            s
+        } else {
+            normalized = s.nfc().collect();
+            &normalized
+        };
         if self.rgx_vec.len() > s.len() {
             return false;
         }
@@ -268,6 +361,171 @@ impl std::fmt::Display for Regex {
     }
 }
 
+/// One element of SkipPattern: a set of characters repeated from ske_min to ske_max times.
+#[derive(Clone)]
+pub struct SkipElement {
+    pub ske_ranges: Vec<(char, char)>, // ranges of characters in the set, e.g. ('0', '9')
+    pub ske_word: bool,                // the set includes the letters, digits and underscore ("\w")
+    pub ske_included: bool,            // false if the set is complemented
+    pub ske_min: u32,
+    pub ske_max: u32,
+}
+
+impl SkipElement {
+    fn new(ske_ranges: Vec<(char, char)>, ske_word: bool, ske_included: bool) -> SkipElement {
+        SkipElement {
+            ske_ranges,
+            ske_word,
+            ske_included,
+            ske_min: 1,
+            ske_max: 1,
+        }
+    }
+
+    fn matches(&self, c: char) -> bool {
+        let in_set = self.ske_ranges.iter().any(|&(first, last)| first <= c && c <= last)
+            || self.ske_word && (c.is_alphanumeric() || c == '_');
+        in_set == self.ske_included
+    }
+}
+
+/// Minimal regular expression for the tokens that are not checked, e.g. "v\d+".
+/// It matches the whole token. The dot "." means any character, "\d" a digit
+/// and "\w" a letter, digit or underscore, the other escaped characters are literal. The brackets "[]" define
+/// a character set, with ranges like "a-z", complemented by the caron "^"
+/// after the opening bracket. The element before "*", "+" or "?" is repeated
+/// any number of times, at least once or at most once.
+/// The other regex punctuation {}()| is not allowed, the first error is kept in skp_error.
+#[derive(Clone)]
+pub struct SkipPattern {
+    pub skp_def: String,                        // definition string
+    pub skp_elements: Vec<SkipElement>,
+    pub skp_error: Option<(&'static str, u32)>, // description and column number (starting with 1)
+}
+
+impl SkipPattern {
+    pub fn new(skp_def: &str) -> SkipPattern {
+        let mut skp_elements: Vec<SkipElement> = vec![];
+        let mut skp_error: Option<(&'static str, u32)> = None;
+        // the anchors are allowed, the whole token is matched anyway
+        let clean_def = skp_def.strip_prefix('^').unwrap_or(skp_def);
+        let clean_def = clean_def.strip_suffix('$').unwrap_or(clean_def);
+        let escaped_ranges = |c: char| -> (Vec<(char, char)>, bool) {
+            match c {
+                'd' => (vec![('0', '9')], false),
+                'w' => (vec![], true),
+                _ => (vec![(c, c)], false),
+            }
+        };
+        let mut chars = clean_def.chars().peekable();
+        let mut pos: u32 = 0;
+        while let Some(c) = chars.next() {
+            pos += 1;
+            if c == '\\' {
+                pos += 1;
+                let Some(escaped) = chars.next() else {
+                    skp_error = Some(("Backslash (\\) at the end of pattern", pos));
+                    break;
+                };
+                let (ranges, word) = escaped_ranges(escaped);
+                skp_elements.push(SkipElement::new(ranges, word, true));
+            } else if c == '[' {
+                let mut ranges: Vec<(char, char)> = vec![];
+                let mut word = false;
+                let mut is_included = true;
+                let mut closed = false;
+                if chars.peek() == Some(&'^') {
+                    chars.next();
+                    pos += 1;
+                    is_included = false;
+                }
+                while let Some(c) = chars.next() {
+                    pos += 1;
+                    if c == ']' {
+                        closed = true;
+                        break;
+                    } else if c == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            pos += 1;
+                            let (escaped_ranges, escaped_word) = escaped_ranges(escaped);
+                            ranges.extend(escaped_ranges);
+                            word |= escaped_word;
+                        }
+                    } else if c == '-' && !ranges.is_empty() && chars.peek().is_some_and(|&next| next != ']') {
+                        let last = chars.next().unwrap_or(c);
+                        pos += 1;
+                        if let Some(range) = ranges.last_mut() {
+                            range.1 = last;
+                        }
+                    } else {
+                        ranges.push((c, c));
+                    }
+                }
+                if !closed {
+                    skp_error = Some(("Open brackets ([) not closed in pattern", pos));
+                }
+                skp_elements.push(SkipElement::new(ranges, word, is_included));
+            } else if c == '.' {
+                skp_elements.push(SkipElement::new(vec![], false, false));
+            } else if "*+?".contains(c) {
+                match skp_elements.last_mut() {
+                    Some(element) if element.ske_min == 1 && element.ske_max == 1 => {
+                        element.ske_min = if c == '+' { 1 } else { 0 };
+                        element.ske_max = if c == '?' { 1 } else { u32::MAX };
+                    }
+                    _ => {
+                        skp_error = Some(("Unexpected repetition (*+?) in pattern", pos));
+                        break;
+                    }
+                }
+            } else if "{}()|]".contains(c) {
+                skp_error = Some(("Unexpected character in pattern", pos));
+                break;
+            } else {
+                skp_elements.push(SkipElement::new(vec![(c, c)], false, true));
+            }
+        }
+        SkipPattern {
+            skp_def: skp_def.to_string(),
+            skp_elements,
+            skp_error,
+        }
+    }
+
+    /// Returns true if the pattern matches the whole token.
+    pub fn match_token(&self, token: &str) -> bool {
+        if self.skp_error.is_some() {
+            return false;
+        }
+        let chars: Vec<char> = token.chars().collect();
+        // the positions in the token reached by the elements matched so far,
+        // each position is tried once, unlike the backtracking of the repetitions
+        let mut reached = vec![false; chars.len() + 1];
+        reached[0] = true;
+        for element in &self.skp_elements {
+            let mut next_reached = vec![false; chars.len() + 1];
+            for start in (0..=chars.len()).filter(|&start| reached[start]) {
+                let max_count = chars[start..]
+                    .iter()
+                    .take(element.ske_max.min(chars.len() as u32) as usize)
+                    .take_while(|&&c| element.matches(c))
+                    .count();
+                for count in element.ske_min as usize..=max_count {
+                    next_reached[start + count] = true;
+                }
+            }
+            reached = next_reached;
+        }
+        reached[chars.len()]
+    }
+}
+
+impl std::fmt::Display for SkipPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.skp_def)
+    }
+}
+
 /// Parsed PFX or SFX non-first line.
 pub struct AffixEntry {
     // flag stripping affix/flags [condition [morphological_fields...]]"
@@ -297,6 +555,103 @@ impl AffixEntry {
             afe_ix: 0,
         }
     }
+
+    /// Returns false if the condition can never be satisfied. The condition is checked
+    /// on the base word, which starts (prefix) or ends (suffix) with the stripped text.
+    /// E.g. "SFX G e ing [^e]" never applies, the base word ends with "e".
+    pub fn condition_fits_strip(&self, is_prefix: bool) -> bool {
+        let cond = &self.afe_cond.rgx_vec;
+        if is_prefix {
+            cond.iter()
+                .zip(self.afe_sub.chars())
+                .all(|(ri, si)| ri.0.contains(si) == ri.1)
+        } else {
+            cond.iter()
+                .rev()
+                .zip(self.afe_sub.chars().rev())
+                .all(|(ri, si)| ri.0.contains(si) == ri.1)
+        }
+    }
+}
+
+/// Parsed PHONE rule, e.g. "AH(AEIOUY)-^ *H" or "CK K", see Spell::phonetic_code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhoneRule {
+    /// the characters matched at the position, e.g. "AH"
+    pub phr_chars: Vec<char>,
+    /// one character of the group matched after phr_chars, e.g. "AEIOUY" for "(AEIOUY)"
+    pub phr_group: Vec<char>,
+    /// the number of the matched characters at the end that are not replaced, one per "-"
+    pub phr_ahead: usize,
+    /// "^", the rule is applied only at the start of the word
+    pub phr_at_start: bool,
+    /// "$", the rule is applied only at the end of the word
+    pub phr_at_end: bool,
+    /// the priority digit, 5 without it, compared with the rule following this one
+    pub phr_priority: u8,
+    /// "<", the replacement is put back into the word and the rules are applied to it again
+    pub phr_restart: bool,
+    /// the replacement, empty for "_"
+    pub phr_replacement: String,
+}
+
+/// Read-only view of an affix class, e.g. for dictionary editors.
+#[derive(Clone, Copy)]
+pub struct AffixClassView<'a> {
+    acv_class: &'a AffixClass,
+}
+
+impl<'a> AffixClassView<'a> {
+    /// The flag naming the class.
+    pub fn name(&self) -> &'a str {
+        &self.acv_class.afc_name
+    }
+
+    /// True for PFX, false for SFX.
+    pub fn is_prefix(&self) -> bool {
+        self.acv_class.afc_is_pre
+    }
+
+    /// True when the prefixes and suffixes can be combined (Y in the header).
+    pub fn cross_product(&self) -> bool {
+        self.acv_class.afc_circum
+    }
+
+    /// The entries in the order of the affix file.
+    pub fn entries(&self) -> impl Iterator<Item = AffixEntryView<'a>> {
+        self.acv_class
+            .afc_affixes
+            .iter()
+            .map(|affix_entry| AffixEntryView { aev_entry: affix_entry })
+    }
+}
+
+/// Read-only view of an affix entry, see AffixClassView.
+#[derive(Clone, Copy)]
+pub struct AffixEntryView<'a> {
+    aev_entry: &'a AffixEntry,
+}
+
+impl<'a> AffixEntryView<'a> {
+    /// The text stripped from the word, empty for "0".
+    pub fn sub(&self) -> &'a str {
+        &self.aev_entry.afe_sub
+    }
+
+    /// The text added to the word, empty for "0".
+    pub fn add(&self) -> &'a str {
+        &self.aev_entry.afe_add
+    }
+
+    /// The condition as written in the affix file, e.g. "[^aeiou]y".
+    pub fn condition(&self) -> &'a str {
+        &self.aev_entry.afe_cond.rgx_def
+    }
+
+    /// The continuation flags, e.g. ["A", "B"] for "s/AB".
+    pub fn next_flags(&self) -> &'a [String] {
+        &self.aev_entry.afe_next_flags
+    }
 }
 
 /// Parsed from the initial line of affix group with data from the next corresponding lines
@@ -358,6 +713,31 @@ impl FlaggedWord {
     }
 }
 
+/// The dictionary keys by their character n-grams for the n-gram suggestions,
+/// see Spell::ngram_suggestions. The keys added after index_words are last.
+#[derive(Default)]
+pub struct NgramIndex {
+    pub ngx_keys: Vec<Arc<str>>,
+    pub ngx_char_counts: Vec<u32>, // length of each key in characters
+    /// the positions in ngx_keys of the keys having the n-gram, see Spell::word_ngrams
+    pub ngx_key_ixs: HashMap<u64, Vec<u32>>,
+}
+
+impl NgramIndex {
+    pub fn add_key(&mut self, key: &Arc<str>) {
+        let chars: Vec<char> = key.chars().collect();
+        let mut ngrams = Spell::word_ngrams(&chars);
+        ngrams.sort_unstable();
+        ngrams.dedup();
+        let key_ix = self.ngx_keys.len() as u32;
+        for ngram in ngrams {
+            self.ngx_key_ixs.entry(ngram).or_default().push(key_ix);
+        }
+        self.ngx_keys.push(key.clone());
+        self.ngx_char_counts.push(chars.len() as u32);
+    }
+}
+
 /// One line from a dic file
 pub struct DicEntry {
     /// Line number in the dictionary file
@@ -365,6 +745,13 @@ pub struct DicEntry {
     /// The line in the dictionary file defining the entry
     pub den_source: String,
     pub den_words: Vec<FlaggedWord>,
+    /// The morphological fields after the words, e.g. "ph:witch"
+    pub den_morph: Vec<String>,
+    /// The single words of the same key in other case, from other lines,
+    /// e.g. "Polish" for "polish", see Parser::parse_dic_line
+    pub den_homonyms: Vec<FlaggedWord>,
+    /// The morphological fields of each homonym, in the order of den_homonyms
+    pub den_homonym_morph: Vec<Vec<String>>,
 }
 
 impl DicEntry {
@@ -373,9 +760,17 @@ impl DicEntry {
             den_line_no,
             den_source,
             den_words: vec![],
+            den_morph: vec![],
+            den_homonyms: vec![],
+            den_homonym_morph: vec![],
         }
     }
 
+    /// Returns the word of a single-word entry and its homonyms differing in case.
+    pub fn flagged_words(&self) -> impl Iterator<Item = &FlaggedWord> {
+        self.den_words.iter().take(1).chain(self.den_homonyms.iter())
+    }
+
     /// The key for the HashMap.
     /// If there are multiple words, they're using separator for joining them.
     pub fn hash_key(&self) -> String {
@@ -399,11 +794,16 @@ pub struct SpellLang {
     pub slg_code: String,
     // combined ModeFlag values
     pub slg_mode_flags: u32,
+    /// combined CheckFeature values of the enabled features, set by the caller with set_features;
+    /// the caller changing it directly renews slg_generation with next_generation
+    pub slg_features: u32,
 
     pub slg_set: String,      // SET element: character set for aff and dic files
     pub slg_flag: FlagFormat, // FLAG element: format of affix flags
     pub slg_try: String,
     pub slg_key: String,
+    /// name of the built-in keyboard layout for the suggestions, instead of KEY, see keyboard_layout
+    pub slg_keyboard: String,
     /// The characters from tag_wordchars can be initial or final characters in words or not.
     /// Typically, these are dot (.), hyphen (-), apostrophe (-), digits (0-9) and similar
     /// that are valid in the middle of words. Also used for adding few letters to the end of numbers.
@@ -417,20 +817,31 @@ pub struct SpellLang {
     pub slg_home: String,
     pub slg_version: String,
     pub slg_cplx_pref: bool, // COMPLEXPREFIXES
-    pub slg_prefix_max: u8,  // maximal number of prefixes that can be removed, see COMPLEXPREFIXES
-    pub slg_suffix_max: u8,  // maximal number of suffixes that can be removed, see COMPLEXPREFIXES
+    pub slg_prefix_max: u32, // maximal number of prefixes that can be removed, see COMPLEXPREFIXES, PREFIXMAX
+    pub slg_suffix_max: u32, // maximal number of suffixes that can be removed, see COMPLEXPREFIXES, SUFFIXMAX
     pub slg_sug_split: bool, // NOSPLITSUGS sets it to false
     pub slg_sug_dots: bool,  // SUGSWITHDOTS sets it to true
     pub slg_rep: Vec<(String, String)>,
     pub slg_phone: Vec<(String, String)>,
+    /// the dictionary keys by their phonetic code, with the PHONE table, see index_words
+    pub slg_phone_codes: HashMap<String, Vec<Arc<str>>>,
     pub slg_iconv: Vec<(String, String)>,
     pub slg_oconv: Vec<(String, String)>,
     pub slg_map: (Vec<String>, bool),   // (array_itself, parsed)
     pub slg_break: (Vec<String>, bool), // (array_itself, parsed)
+    /// the parsed slg_map members, the interchangeable characters or character sequences,
+    /// e.g. ["u", "ú", "ü"] for "MAP uúü" or ["ss", "ß"] for "MAP (ss)(ß)"
+    pub slg_map_groups: Vec<Vec<String>>,
+    pub slg_hyph: Vec<String>, // NEA HYPH, the patterns as written
+    /// the parsed slg_hyph patterns, the letters with the levels of the positions between them,
+    /// e.g. "hyph" with [0, 0, 3, 0, 0] for "NEA HYPH hy3ph", see hyphenate
+    pub slg_hyph_patterns: HashMap<String, Vec<u8>>,
     pub slg_af_parsed: bool,
     pub slg_af: Vec<String>,
     pub slg_compoundrule_parsed: bool,
     pub slg_compoundrule: Vec<String>,
+    /// the parsed slg_compoundrule members
+    pub slg_compoundrule_items: Vec<Vec<CompoundRuleItem>>,
     pub slg_comp_check_dup: bool,
     pub slg_comp_check_rep: bool,
     pub slg_comp_check_case: bool,
@@ -442,17 +853,53 @@ pub struct SpellLang {
     pub slg_comp_more_suffixes: bool,
     pub slg_comp_min: u32,
     pub slg_comp_word_max: u32,
+    /// COMPOUNDSYLLABLE, the maximal number of syllables of a compound and the vowels,
+    /// e.g. (6, "aeiou") for "COMPOUNDSYLLABLE 6 aeiou", see syllable_count
+    pub slg_comp_syllable: (u32, String),
+    /// linking elements between compound parts, e.g. "s" in "Arbeitstag", see NEA COMPOUNDLINK
+    pub slg_comp_linkers: Vec<String>,
+    /// the prefixes and suffixes split from the words by the tokenizer, e.g. "USB-" in "USB-enabled",
+    /// see NEA SOFTPREFIX and NEA SOFTSUFFIX
+    pub slg_soft_prefixes: Vec<String>,
+    pub slg_soft_suffixes: Vec<String>,
     pub slg_max_cpd_sugs: u32,
+    /// MAXNGRAMSUGS, the maximal number of the phonetic and n-gram suggestions, by default 4,
+    /// with 0 there are none
     pub slg_max_ngram_sugs: u32,
     pub slg_max_diff: u32,
+    /// the common misspellings of the dictionary words, from their ph: fields,
+    /// e.g. "witch" to "which" for "which ph:witch"
+    pub slg_ph_hints: HashMap<String, String>,
+    /// maximal number of suggestions for a word, by default 5, set by the caller;
+    /// the phonetic and n-gram suggestions are limited by the smaller of it and MAXNGRAMSUGS
+    pub slg_suggest_max: u32,
+    /// maximal length of a checked word in characters, the longer tokens aren't checked
+    /// nor corrected, e.g. in adversarial input, by default 100, set by the caller
+    pub slg_word_len_max: usize,
+    /// the tokens matching any of the patterns aren't checked, e.g. "v\d+" for versions, set by the caller
+    pub slg_skip_patterns: Vec<SkipPattern>,
     pub slg_aff_groups: Vec<AffixClass>, // storage for affixes
     pub slg_pfxes: Vec<u32>,             // indexes of prefixes in slg_aff_groups
     pub slg_sfxes: Vec<u32>,             // indexes of suffixes in slg_aff_groups
     pub slg_flag_hash: HashMap<String, (FlagType, u32)>, // (afg_name, type, afg_ix)
     pub slg_affix_ct: u32,
     pub slg_dic_count: u32,
-    pub slg_dic_hash: HashMap<String, DicEntry>,
+    /// the entries by their case-normalized key, shared with the indexes, see index_words;
+    /// the caller changing them directly, not with add_word, renews slg_generation with next_generation
+    pub slg_dic_hash: HashMap<Arc<str>, DicEntry>,
     pub slg_dic_duplicated: u32, // number of duplicated entries
+    /// sorted keys of the single-word entries in slg_dic_hash, see index_words
+    pub slg_word_index: Vec<Arc<str>>,
+    /// the keys of slg_word_index by their n-grams, see index_words
+    pub slg_ngram_index: NgramIndex,
+    /// the mixed-case keys, e.g. "iPod", by their lowercase, see index_words
+    pub slg_mixed_case_keys: HashMap<String, Vec<Arc<str>>>,
+    /// characters of the dictionary words, the most frequent first, at most WORD_CHARS_MAX, see index_words
+    pub slg_word_chars: Vec<char>,
+    /// unique among the dictionaries, renewed when the words or slg_features are changed,
+    /// see next_generation and WordCache; add_word and set_features renew it,
+    /// any other change of the dictionary must set it to next_generation
+    pub slg_generation: u32,
     pub slg_noparse_tags: HashMap<String, u32>, // tags not set parsed
     pub slg_noparse_flags: HashMap<String, u32>, // flags in dictionary not known
 }
@@ -462,10 +909,12 @@ impl SpellLang {
         SpellLang {
             slg_code: String::from(slg_code),
             slg_mode_flags: 0,
+            slg_features: CheckFeature::ALL,
             slg_set: String::from("UTF-8"),
-            slg_flag: FlagFormat::SingleUni,
+            slg_flag: FlagFormat::SingleChar,
             slg_try: String::from(""),
             slg_key: String::from(""),
+            slg_keyboard: String::from(""),
             tag_wordchars: String::from(""),
             slg_wordchar_digits: false,
             slg_wordchars: vec![],
@@ -480,14 +929,19 @@ impl SpellLang {
             slg_sug_dots: false,
             slg_rep: vec![],
             slg_phone: vec![],
+            slg_phone_codes: HashMap::default(),
             slg_map: (vec![], false),
             slg_break: (vec![], false),
+            slg_map_groups: vec![],
+            slg_hyph: vec![],
+            slg_hyph_patterns: HashMap::default(),
             slg_iconv: vec![],
             slg_oconv: vec![],
             slg_af_parsed: false,
             slg_af: vec![],
             slg_compoundrule_parsed: false,
             slg_compoundrule: vec![],
+            slg_compoundrule_items: vec![],
             slg_comp_check_dup: false,
             slg_comp_check_rep: false,
             slg_comp_check_case: false,
@@ -499,266 +953,2581 @@ impl SpellLang {
             slg_comp_more_suffixes: false,
             slg_comp_min: 0,
             slg_comp_word_max: 0,
+            slg_comp_syllable: (0, String::new()),
+            slg_comp_linkers: vec![],
+            slg_soft_prefixes: vec![],
+            slg_soft_suffixes: vec![],
             slg_max_cpd_sugs: 0,
-            slg_max_ngram_sugs: 0,
+            slg_max_ngram_sugs: 4,
             slg_max_diff: 5,
+            slg_ph_hints: HashMap::default(),
+            slg_suggest_max: 5,
+            slg_word_len_max: 100,
+            slg_skip_patterns: vec![],
             slg_pfxes: vec![],
             slg_sfxes: vec![],
             slg_aff_groups: vec![],
-            slg_flag_hash: HashMap::new(),
+            slg_flag_hash: HashMap::default(),
             slg_affix_ct: 0,
             slg_dic_count: 0,
-            slg_dic_hash: HashMap::new(),
+            slg_dic_hash: HashMap::default(),
             slg_dic_duplicated: 0,
-            slg_noparse_tags: HashMap::new(),
+            slg_word_index: vec![],
+            slg_ngram_index: NgramIndex::default(),
+            slg_mixed_case_keys: HashMap::default(),
+            slg_word_chars: vec![],
+            slg_generation: Self::next_generation(),
+            slg_noparse_tags: HashMap::default(),
             // temporarily tracking the tags that are not yet implemented
             // also can be used for ordering between tags
-            slg_noparse_flags: HashMap::new(),
+            slg_noparse_flags: HashMap::default(),
         }
     }
-}
-
-#[derive(PartialEq, Copy, Clone)]
-pub enum TokenType {
-    NotWord,
-    IsWord,
-    IsGoodWord, // spelling-check passed
-    IsBadWord, // spelling-check failed
-}
-
-/// Functions for spelling words and suggesting corrections.
-pub struct Spell {}
 
-impl Spell {
-    /// The function returns true if the word is present in the dictionary
-    /// and (optionally) if it has the required flag.
-    /// todo: process multi-word entries
-    fn word_present(
-        spell_lang: &SpellLang,
-        char_case: CharCase,
-        word: &str,
-        flag: Option<&String>,
-    ) -> bool {
-        let dict_entry = spell_lang.slg_dic_hash.get(word);
-        if let Some(dict_entry) = dict_entry {
-            let dict_case = dict_entry.den_words[0].flw_char_case;
-            if dict_case == CharCase::Upper {
-                if char_case == CharCase::Initial {
-                    // the uppercase abbreviations (in dictionary) are not allowed with initial case (in text)
-                    // todo define Modeflag value to allow in identifiers in programming languages like ParseHtml
-                    return false;
-                }
-            }
-            if dict_case == CharCase::Upper || dict_case == CharCase::Initial {
-                if (spell_lang.slg_mode_flags as u32 & ModeFlag::TestCompat as u32) != 0
-                    && char_case == CharCase::Lower
-                {
-                    //mail addresses and other internet identificators are lowercase
-                    // such lowercase is not allowed in ModeFlag::TestCompat
-                    return false;
-                }
-            }
-            if let Some(flag) = flag {
-                return dict_entry.den_words[0].flw_flags.contains(&flag);
-            }
-            return true; // no flags to check
+    /// Splits string with multiple flags.
+    /// Without FLAG and with FLAG UTF-8, each flag is one character, multiple flags are not separated.
+    /// With FLAG long, each flag is two characters, multiple flags are not separated
+    /// With FLAG num, each flag is an unsigned number, multiple flags are separated by commas
+    pub fn split_flags(&self, flags: &str) -> Vec<String> {
+        if flags.is_empty() {
+            return vec![];
+        }
+        if self.slg_flag == FlagFormat::SingleChar || self.slg_flag == FlagFormat::SingleUni {
+            // one-character flags
+            return flags.chars().map(|c| c.to_string()).collect();
+        }
+        if self.slg_flag == FlagFormat::DoubleChar {
+            // two-character flags, an unpaired last character is dropped
+            let chars: Vec<char> = flags.chars().collect();
+            return chars
+                .chunks_exact(2)
+                .map(|pair| pair.iter().collect())
+                .collect();
         }
-        false // word not in dictionary
+        if self.slg_flag == FlagFormat::Numeric {
+            return flags.split(",").map(|s| s.to_string()).collect();
+        }
+        vec![]
     }
 
-    /// Returns true if 'substring' is at the start or at the end of 'word',
-    /// depending on 'is_prefix'.
-    fn is_substring_at_edge(word: &str, substring: &str, is_prefix: bool) -> bool {
-        if is_prefix {
-            word.starts_with(substring)
-        } else {
-            word.ends_with(substring)
-        }
+    /// Returns true if the word has the flag, directly or by the flag alias (AF).
+    pub fn word_has_flag(&self, flagged_word: &FlaggedWord, flag: &str) -> bool {
+        self.flags_contain(&flagged_word.flw_flags, flag)
     }
 
-    /// The function returns true if the word is correctly spelled in spell_lang
-    /// and (for languages with uppercase and lowercase letters)
-    /// has the character case as in the dictionary.
-    /// Thus far, some amount of prefixes (prefix_ct) or suffixes 8suffix_ct) has already been removed from the original word.
-    /// For the second affix of the same place, only affix groups in ix_subset are allowed.
-    fn check_decased_word(
-        spell_lang: &SpellLang,
-        mut char_case: CharCase,
-        word: &str,
-        ix_subset: Option<&Vec<u32>>,
-        prefix_ct: u8, // so many prefixes has been processed
-        suffix_ct: u8, // so many prefixes has been processed
-    ) -> bool {
-        if Spell::word_present(spell_lang, char_case, word, None) && ix_subset == None {
-            return true;
-        }
-        let mut base_word = String::with_capacity(128); // not to allocate it often, it's defined here
-                                                        // after removing affix from a word with other casing, the casing of the new word can be different
-        let originally_other_case = char_case == CharCase::Other;
-        for affix_group in &spell_lang.slg_aff_groups {
-            let new_prefix_ct = if affix_group.afc_is_pre {
-                prefix_ct + 1
-            } else {
-                prefix_ct
-            };
-            let new_suffix_ct = if affix_group.afc_is_pre {
-                suffix_ct
-            } else {
-                suffix_ct + 1
-            };
-            // new_prefix_ct and new_suffix_ct are the counts after applying any affix_entry from affix_group
-            if new_prefix_ct > spell_lang.slg_prefix_max
-                || new_suffix_ct > spell_lang.slg_suffix_max
-            {
-                continue; // this would be too many levels for prefixes or suffixes
+    /// Returns true if the flags contain the flag, directly or by the flag alias (AF).
+    pub fn flags_contain(&self, flags: &[String], flag: &str) -> bool {
+        for word_flag in flags {
+            if word_flag == flag {
+                return true;
             }
-            if new_prefix_ct == 2 || new_suffix_ct == 2 {
-                // when applying the second affix of the same place, only some affixes are allowed
-                if let Some(subset) = ix_subset {
-                    if !subset.contains(&affix_group.afc_ix) {
-                        continue; // skip such affix group, not in a vector of required indexes
+            if let Some((FlagType::FlagAf, af_ix)) = self.slg_flag_hash.get(word_flag) {
+                if let Some(af_flags) = self.slg_af.get(*af_ix as usize) {
+                    if self.split_flags(af_flags).iter().any(|af_flag| af_flag == flag) {
+                        return true;
                     }
                 }
             }
-            for affix_entry in &affix_group.afc_affixes {
-                if !Spell::is_substring_at_edge(word, &affix_entry.afe_add, affix_group.afc_is_pre)
-                {
-                    continue;
-                }
-                // from word to base_word: -add, +sub
-                base_word.clear();
-                if affix_group.afc_is_pre {
-                    base_word += &affix_entry.afe_sub;
-                    base_word += &word[affix_entry.afe_add.len()..];
-                } else {
-                    base_word += &word[..word.len() - affix_entry.afe_add.len()];
-                    base_word += &affix_entry.afe_sub;
-                }
-                if originally_other_case {
-                    (char_case, base_word) = CharCase::normalize_case(&base_word);
-                }
-                // now check the base_word
-                if !affix_entry
-                    .afe_cond
-                    .match_edge(&base_word, affix_group.afc_is_pre)
-                {
-                    continue;
-                }
-                if Spell::word_present(
-                    spell_lang,
-                    char_case,
-                    &base_word,
-                    Some(&affix_group.afc_name),
-                ) {
-                    return true;
-                }
-                if Spell::check_decased_word(
-                    spell_lang,
-                    char_case,
-                    &base_word,
-                    Some(&affix_group.afc_prev_flags),
-                    new_prefix_ct,
-                    new_suffix_ct,
-                ) {
-                    return true;
-                }
-            }
         }
-        // lng_mode_flags
         false
     }
 
-    /// Returns true if the (non-alphabetic) character can be either in a word or not.
-    /// There are two spaces in example 'It's five o'clock.' so three token are produced.
-    /// In the first token ('It's), the first apostrophe is not part of word,
-    /// the second one is part of word.
-    fn is_non_alphabetic_in_word(spell_lang: &SpellLang, c: char) -> bool {
-        spell_lang.slg_wordchar_digits && c.is_ascii_digit()
-            || spell_lang.slg_wordchars.contains(&c)
+    /// Returns true if the dictionary has the word with the flag, directly or by the flag alias (AF).
+    /// The word is looked up case-normalized, affixes are not removed.
+    pub fn has_word_with_flag(&self, word: &str, flag: &str) -> bool {
+        let (_, key) = CharCase::decompose(word);
+        match self.slg_dic_hash.get(key.as_str()) {
+            Some(dic_entry) => dic_entry
+                .den_words
+                .iter()
+                .any(|flagged_word| self.word_has_flag(flagged_word, flag)),
+            None => false,
+        }
     }
 
-    // Returns true if the character can be in a word.
-    fn in_word_or_optional(spell_lang: &SpellLang, c: char) -> bool {
-        c.is_alphabetic() || Spell::is_non_alphabetic_in_word(spell_lang, c)
+    /// Loads the dictionary from the aff and dic text, without accessing the file system.
+    /// The notes are not kept, see TextParser::parse_lang_readers.
+    pub fn from_readers(aff: impl LineReader, dic: impl LineReader) -> SpellLang {
+        TextParser::new().parse_lang_readers(aff, dic)
     }
 
-    pub fn check_token(spell_lang: &SpellLang, word: &str) -> bool {
-        if word.len() == 0 {
-            return true;
-        }
-        /*
-        - Dictionary forms of the words can be uppercased in general text:
-        test, Test TEST
-        London london LONDON
-        HTML
-        - Letters within the word can be uppercased:
-        's-Gravenhage 'S-GRAVENHAGE
-        TikTok TIKTOK
-        - Dictionary forms of the words can be lowercased in internet addresses:
-        UNICEF (@unicef), unicef.org
-        - Full sentences with mixed character case
-        I visited the UNICEF web page unicef.org.
-        The official name of the Hague is 's-Gravenhage.
-        TikTok is well known.
+    /// Returns the minimal length of a compound part in characters,
+    /// set by COMPOUNDMIN, by default 3.
+    pub fn compound_min(&self) -> usize {
+        if self.slg_comp_min == 0 {3} else {self.slg_comp_min as usize}
+    }
 
-        */
-        let (char_case, normalized_word) = CharCase::normalize_case(word);
-        let mut result =
-            Spell::check_decased_word(&spell_lang, char_case, &normalized_word, None, 0, 0);
-        if !result {
-            // let's trim the characters that are optionally in the word
-            let trimmed_word =
-                &normalized_word.trim_matches(|c| Spell::is_non_alphabetic_in_word(spell_lang, c));
-            result = Spell::check_decased_word(&spell_lang, char_case, trimmed_word, None, 0, 0);
-        }
-        //     fn is_non_alphabetic_in_word(&self, c:char) -> bool {
+    /// Returns true if the compound part is not shorter than COMPOUNDMIN. The part is
+    /// measured as written in the compound word, also when it's an affixed form
+    /// of a shorter stem, e.g. "cats" (not "cat") with COMPOUNDMIN 4.
+    pub fn compound_part_fits_min(&self, part: &str) -> bool {
+        part.chars().count() >= self.compound_min()
+    }
 
-        result
+    /// Returns the remainders of the compound after a linking element at its start,
+    /// e.g. "tag" for "stag" with "NEA COMPOUNDLINK s es". The linking element
+    /// is tried after a compound part, before the next part.
+    pub fn strip_compound_linkers<'s, 'w: 's>(&'s self, rest: &'w str) -> impl Iterator<Item = &'w str> + 's {
+        self.slg_comp_linkers
+            .iter()
+            .filter_map(move |linker| rest.strip_prefix(linker.as_str()))
+            .filter(|remainder| !remainder.is_empty())
     }
 
-    /// Changes `untokenized_text` into a vector of tuples
-    /// Vec<(a_string_of_charactes: String, token_type: TokenType)>
-    fn tokenize(spell_lang: &SpellLang, untokenized_text: &str) -> Vec<(String, TokenType)> {
-        let parts = 
-            untokenized_text.match_indices(|c: char| !Spell::in_word_or_optional(spell_lang, c));
-        let mut token_vec = Vec::<(String, TokenType)>::new();
-        let mut last_ix: usize = 0; // end of last pushed non-word
-        for part in parts {
-            let (start_ix, word) = part;
-            if last_ix < start_ix {
-                token_vec.push ((untokenized_text[last_ix..start_ix].to_string(), TokenType::IsWord));
-            }
-            token_vec.push ((word.to_string(), TokenType::NotWord));
-            last_ix = start_ix + word.len();
-        }
-        if last_ix < untokenized_text.len() {
-            token_vec.push ((untokenized_text[last_ix..].to_string(), TokenType::IsWord));
+    /// Returns the class of the character for the words of the language, by WORDCHARS and IGNORE.
+    pub fn char_class(&self, c: char) -> CharClass {
+        Tokenizer::from_lang(self).char_class(c)
+    }
+
+    /// Returns the counts and other data about the loaded dictionary.
+    pub fn summary(&self) -> Summary {
+        Summary {
+            smr_encoding: self.slg_set.clone(),
+            smr_flag_count: self.slg_flag_hash.len(),
+            smr_affix_entry_count: self.slg_affix_ct,
+            smr_word_count: self.slg_dic_hash.len(),
+            smr_duplicate_count: self.slg_dic_duplicated,
+            smr_unknown_tags: self.slg_noparse_tags.clone(),
+            smr_unknown_flags: self.slg_noparse_flags.clone(),
         }
-        token_vec
     }
 
-    /// Check several words or paragraph, not yet tokenized.
-    pub fn check_text<'a>(
-        spell_lang: &SpellLang,
-        untokenized_text: &'a str,
-    ) -> Vec<(String, TokenType)> {
-        let mut tokens: Vec<(String, TokenType)> = Spell::tokenize(spell_lang, &untokenized_text);
-        for token in &mut tokens {
-            let (word, token_type) = token;
-            if word.len() == 0 || *token_type != TokenType::IsWord {
+    /// Returns the approximate number of heap bytes used by the dictionary words
+    /// (slg_dic_hash and its indexes), the affixes (slg_aff_groups), the flag tables
+    /// (slg_flag_hash and the AF table) and the HYPH patterns,
+    /// e.g. to budget the memory for many loaded languages.
+    /// The allocator overhead and the other tables aren't counted.
+    pub fn memory_estimate(&self) -> usize {
+        use std::mem::size_of;
+        let strings_bytes = |strings: &Vec<String>| {
+            strings.capacity() * size_of::<String>() + strings.iter().map(String::capacity).sum::<usize>()
+        };
+        let flagged_words_bytes = |flagged_words: &Vec<FlaggedWord>| {
+            flagged_words.capacity() * size_of::<FlaggedWord>()
+                + flagged_words
+                    .iter()
+                    .map(|flagged_word| flagged_word.flw_word.capacity() + strings_bytes(&flagged_word.flw_flags))
+                    .sum::<usize>()
+        };
+        // a hash table has a byte of control data for each bucket
+        let keys_by_string_bytes = |keys_by_string: &HashMap<String, Vec<Arc<str>>>| {
+            keys_by_string.capacity() * (size_of::<String>() + size_of::<Vec<Arc<str>>>() + 1)
+                + keys_by_string
+                    .iter()
+                    .map(|(string, keys)| string.capacity() + keys.capacity() * size_of::<Arc<str>>())
+                    .sum::<usize>()
+        };
+        let dic_bytes = self.slg_dic_hash.capacity() * (size_of::<Arc<str>>() + size_of::<DicEntry>() + 1)
+            + self
+                .slg_dic_hash
+                .iter()
+                .map(|(key, dict_entry)| {
+                    // the shared key has two reference counts before the text
+                    2 * size_of::<usize>()
+                        + key.len()
+                        + dict_entry.den_source.capacity()
+                        + flagged_words_bytes(&dict_entry.den_words)
+                        + strings_bytes(&dict_entry.den_morph)
+                        + flagged_words_bytes(&dict_entry.den_homonyms)
+                        + dict_entry.den_homonym_morph.capacity() * size_of::<Vec<String>>()
+                        + dict_entry.den_homonym_morph.iter().map(strings_bytes).sum::<usize>()
+                })
+                .sum::<usize>()
+            + self.slg_word_index.capacity() * size_of::<Arc<str>>()
+            + self.slg_ngram_index.ngx_keys.capacity() * size_of::<Arc<str>>()
+            + self.slg_ngram_index.ngx_char_counts.capacity() * size_of::<u32>()
+            + self.slg_ngram_index.ngx_key_ixs.capacity() * (size_of::<u64>() + size_of::<Vec<u32>>() + 1)
+            + self
+                .slg_ngram_index
+                .ngx_key_ixs
+                .values()
+                .map(|key_ixs| key_ixs.capacity() * size_of::<u32>())
+                .sum::<usize>()
+            + keys_by_string_bytes(&self.slg_phone_codes)
+            + keys_by_string_bytes(&self.slg_mixed_case_keys);
+        let affix_bytes = self.slg_aff_groups.capacity() * size_of::<AffixClass>()
+            + self
+                .slg_aff_groups
+                .iter()
+                .map(|affix_class| {
+                    affix_class.afc_name.capacity()
+                        + affix_class.afc_prev_flags.capacity() * size_of::<u32>()
+                        + affix_class.afc_affixes.capacity() * size_of::<AffixEntry>()
+                        + affix_class
+                            .afc_affixes
+                            .iter()
+                            .map(|affix_entry| {
+                                affix_entry.afe_sub.capacity()
+                                    + affix_entry.afe_add.capacity()
+                                    + strings_bytes(&affix_entry.afe_next_flags)
+                                    + affix_entry.afe_cond.rgx_def.capacity()
+                                    + affix_entry.afe_cond.rgx_vec.capacity() * size_of::<(String, bool)>()
+                                    + affix_entry.afe_cond.rgx_vec.iter().map(|(chars, _)| chars.capacity()).sum::<usize>()
+                                    + strings_bytes(&affix_entry.afe_morph)
+                            })
+                            .sum::<usize>()
+                })
+                .sum::<usize>();
+        let flag_bytes = self.slg_flag_hash.capacity() * (size_of::<String>() + size_of::<(FlagType, u32)>() + 1)
+            + self.slg_flag_hash.keys().map(String::capacity).sum::<usize>()
+            + strings_bytes(&self.slg_af);
+        let hyph_bytes = self.slg_hyph_patterns.capacity() * (size_of::<String>() + size_of::<Vec<u8>>() + 1)
+            + self
+                .slg_hyph_patterns
+                .iter()
+                .map(|(pattern, levels)| pattern.capacity() + levels.capacity())
+                .sum::<usize>();
+        dic_bytes + affix_bytes + flag_bytes + hyph_bytes
+    }
+
+    /// Returns true if the word isn't longer than slg_word_len_max characters.
+    /// The longer word is not counted to its end.
+    pub fn fits_word_len_max(&self, word: &str) -> bool {
+        word.chars().nth(self.slg_word_len_max).is_none()
+    }
+
+    /// Returns true if the compound with 'part_count' words is allowed by COMPOUNDWORDMAX.
+    /// Without the tag (value 0), the number of words is not limited.
+    pub fn compound_fits_word_max(&self, part_count: usize) -> bool {
+        self.slg_comp_word_max == 0 || part_count <= self.slg_comp_word_max as usize
+    }
+
+    /// Returns true if the compound of more words than COMPOUNDWORDMAX is allowed
+    /// by COMPOUNDSYLLABLE, having at most its number of syllables, as in Hunspell.
+    pub fn compound_fits_syllable_max(&self, syllables: usize) -> bool {
+        self.slg_comp_syllable.0 != 0 && syllables <= self.slg_comp_syllable.0 as usize
+    }
+
+    /// Returns the syllables of a compound part for COMPOUNDSYLLABLE, 0 without the tag.
+    fn compound_syllables(&self, part: &str) -> usize {
+        if self.slg_comp_syllable.0 == 0 {
+            0
+        } else {
+            self.syllable_count(part)
+        }
+    }
+
+    /// Returns the flags allowing a stem at the position in a compound: COMPOUNDFLAG anywhere,
+    /// COMPOUNDBEGIN for the first part, COMPOUNDMIDDLE for the inner parts
+    /// and COMPOUNDEND (or its older name COMPOUNDLAST) for the last part.
+    pub fn compound_position_flags(&self, is_first: bool, is_last: bool) -> Vec<&str> {
+        let position_types: &[FlagType] = if is_first {
+            &[FlagType::FlagCompBegin]
+        } else if is_last {
+            &[FlagType::FlagCompEnd, FlagType::FlagCompLast]
+        } else {
+            &[FlagType::FlagCompMid]
+        };
+        std::iter::once(FlagType::FlagCompound)
+            .chain(position_types.iter().cloned())
+            .filter_map(|flag_type| self.special_flag(flag_type))
+            .collect()
+    }
+
+    /// Returns the flag defined for the flag type, e.g. "!" for "NOSUGGEST !".
+    pub fn special_flag(&self, flag_type: FlagType) -> Option<&str> {
+        self.slg_flag_hash
+            .iter()
+            .find(|(_, (hash_type, _))| *hash_type == flag_type)
+            .map(|(flag, _)| flag.as_str())
+    }
+
+    /// Returns the stems of the single-word dictionary entries having the flag,
+    /// on the word or on one of its homonyms, in no particular order.
+    pub fn stems_with_flag<'s>(&'s self, flag: &'s str) -> impl Iterator<Item = &'s str> {
+        self.slg_dic_hash
+            .iter()
+            .filter(move |(_, dic_entry)| {
+                dic_entry.den_words.len() == 1
+                    && dic_entry.flagged_words().any(|flagged_word| self.word_has_flag(flagged_word, flag))
+            })
+            .map(|(key, _)| &**key)
+    }
+
+    /// Returns the affix classes in the order of the affix file.
+    pub fn affix_classes(&self) -> impl Iterator<Item = AffixClassView<'_>> {
+        self.slg_aff_groups
+            .iter()
+            .map(|affix_class| AffixClassView { acv_class: affix_class })
+    }
+
+    /// Returns the single-word dictionary entries with their dictionary case,
+    /// in no particular order.
+    pub fn words(&self) -> impl Iterator<Item = String> + '_ {
+        self.slg_dic_hash
+            .iter()
+            .filter(|(_, dic_entry)| dic_entry.den_words.len() == 1)
+            .flat_map(|(key, dic_entry)| {
+                dic_entry
+                    .flagged_words()
+                    .map(move |flagged_word| CharCase::restore_case(flagged_word.flw_char_case, key))
+            })
+    }
+
+    /// Joins the flags as they are written in the aff and dic files, separated by commas
+    /// with FLAG num, otherwise not separated.
+    pub fn join_flags(&self, flags: &[String]) -> String {
+        if self.slg_flag == FlagFormat::Numeric {
+            flags.join(",")
+        } else {
+            flags.concat()
+        }
+    }
+
+    /// Returns the loaded affix data in the Hunspell aff format, always in UTF-8.
+    /// The extensions of neaspell are written as NEA lines, the tags not parsed
+    /// on loading (see slg_noparse_tags) are not written.
+    pub fn export_aff(&self) -> String {
+        let mut lines: Vec<String> = vec![String::from("SET UTF-8")];
+        match self.slg_flag {
+            FlagFormat::SingleChar => {}
+            FlagFormat::SingleUni => lines.push(String::from("FLAG UTF-8")),
+            FlagFormat::DoubleChar => lines.push(String::from("FLAG long")),
+            FlagFormat::Numeric => lines.push(String::from("FLAG num")),
+        }
+        let string_tags = [
+            ("TRY", &self.slg_try),
+            ("LANG", &self.slg_code),
+            ("KEY", &self.slg_key),
+            ("WORDCHARS", &self.tag_wordchars),
+            ("IGNORE", &self.slg_ignore),
+            ("NAME", &self.slg_name),
+            ("HOME", &self.slg_home),
+            ("VERSION", &self.slg_version),
+        ];
+        for (tag, value) in string_tags {
+            if !value.is_empty() {
+                lines.push(format!("{tag} {value}"));
+            }
+        }
+        let bool_tags = [
+            ("COMPLEXPREFIXES", self.slg_cplx_pref),
+            ("NOSPLITSUGS", !self.slg_sug_split),
+            ("SUGSWITHDOTS", self.slg_sug_dots),
+            ("CHECKCOMPOUNDDUP", self.slg_comp_check_dup),
+            ("CHECKCOMPOUNDREP", self.slg_comp_check_rep),
+            ("CHECKCOMPOUNDCASE", self.slg_comp_check_case),
+            ("CHECKSHARPS", self.slg_check_sharp_s),
+            ("CHECKCOMPOUNDTRIPLE", self.slg_check_comp_triple),
+            ("SIMPLIFIEDTRIPLE", self.slg_simplified_triple),
+            ("ONLYMAXDIFF", self.slg_only_max_diff),
+            ("FULLSTRIP", self.slg_full_string),
+            ("COMPOUNDMORESUFFIXES", self.slg_comp_more_suffixes),
+        ];
+        for (tag, value) in bool_tags {
+            if value {
+                lines.push(tag.to_string());
+            }
+        }
+        // the default limits, see COMPLEXPREFIXES
+        let (prefix_max, suffix_max) = if self.slg_cplx_pref { (2, 1) } else { (1, 2) };
+        let number_tags = [
+            ("COMPOUNDMIN", self.slg_comp_min, 0),
+            ("COMPOUNDWORDMAX", self.slg_comp_word_max, 0),
+            ("MAXCPDSUGS", self.slg_max_cpd_sugs, 0),
+            ("MAXNGRAMSUGS", self.slg_max_ngram_sugs, 4),
+            ("MAXDIFF", self.slg_max_diff, 5),
+            ("PREFIXMAX", self.slg_prefix_max, prefix_max),
+            ("SUFFIXMAX", self.slg_suffix_max, suffix_max),
+        ];
+        for (tag, value, default_value) in number_tags {
+            if value != default_value {
+                lines.push(format!("{tag} {value}"));
+            }
+        }
+        let nea_tags = [
+            ("COMPOUNDLINK", &self.slg_comp_linkers),
+            ("SOFTPREFIX", &self.slg_soft_prefixes),
+            ("SOFTSUFFIX", &self.slg_soft_suffixes),
+            ("HYPH", &self.slg_hyph),
+        ];
+        for (tag, values) in nea_tags {
+            if !values.is_empty() {
+                lines.push(format!("NEA {tag} {}", values.join(" ")));
+            }
+        }
+        if !self.slg_comp_syllable.1.is_empty() {
+            lines.push(format!("COMPOUNDSYLLABLE {} {}", self.slg_comp_syllable.0, self.slg_comp_syllable.1));
+        }
+        let string_tables = [("MAP", &self.slg_map), ("BREAK", &self.slg_break)];
+        for (tag, (values, parsed)) in string_tables {
+            if *parsed {
+                lines.push(format!("{tag} {}", values.len()));
+                lines.extend(values.iter().map(|value| format!("{tag} {value}")));
+            }
+        }
+        let pair_tables = [
+            ("REP", &self.slg_rep),
+            ("PHONE", &self.slg_phone),
+            ("ICONV", &self.slg_iconv),
+            ("OCONV", &self.slg_oconv),
+        ];
+        for (tag, pairs) in pair_tables {
+            if !pairs.is_empty() {
+                lines.push(format!("{tag} {}", pairs.len()));
+                lines.extend(pairs.iter().map(|(from, to)| format!("{tag} {from} {to}")));
+            }
+        }
+        if self.slg_af_parsed {
+            lines.push(format!("AF {}", self.slg_af.len()));
+            lines.extend(self.slg_af.iter().map(|af_flags| format!("AF {af_flags}")));
+        }
+        if self.slg_compoundrule_parsed {
+            lines.push(format!("COMPOUNDRULE {}", self.slg_compoundrule.len()));
+            lines.extend(self.slg_compoundrule.iter().map(|rule| format!("COMPOUNDRULE {rule}")));
+        }
+        for (tag, flag_type) in &Parser::SIMPLE_FLAG_TAGS {
+            let mut flags: Vec<&String> = self
+                .slg_flag_hash
+                .iter()
+                .filter(|(_, (hash_type, _))| hash_type == flag_type)
+                .map(|(flag, _)| flag)
+                .collect();
+            flags.sort_unstable();
+            lines.extend(flags.iter().map(|flag| format!("{tag} {flag}")));
+        }
+        for affix_class in &self.slg_aff_groups {
+            let tag = if affix_class.afc_is_pre { "PFX" } else { "SFX" };
+            let name = &affix_class.afc_name;
+            let cross_product = if affix_class.afc_circum { "Y" } else { "N" };
+            lines.push(format!("{tag} {name} {cross_product} {}", affix_class.afc_affixes.len()));
+            for affix_entry in &affix_class.afc_affixes {
+                let sub = if affix_entry.afe_sub.is_empty() { "0" } else { &affix_entry.afe_sub };
+                let mut add = if affix_entry.afe_add.is_empty() { "0" } else { &affix_entry.afe_add }.to_string();
+                if !affix_entry.afe_next_flags.is_empty() {
+                    add += "/";
+                    add += &self.join_flags(&affix_entry.afe_next_flags);
+                }
+                let cond = &affix_entry.afe_cond.rgx_def;
+                let cond = if cond.is_empty() { "." } else { cond };
+                let mut line = format!("{tag} {name} {sub} {add} {cond}");
+                for morph in &affix_entry.afe_morph {
+                    line += " ";
+                    line += morph;
+                }
+                lines.push(line);
+            }
+        }
+        lines.iter().map(|line| line.clone() + "\n").collect()
+    }
+
+    /// Returns the loaded dictionary words in the Hunspell dic format, with the count line,
+    /// in the order of the dic file. The words added after loading are first.
+    pub fn export_dic(&self) -> String {
+        let mut dic_entries: Vec<&DicEntry> = self.slg_dic_hash.values().collect();
+        dic_entries.sort_unstable_by_key(|dic_entry| (dic_entry.den_line_no, dic_entry.hash_key()));
+        let homonym_count: usize = dic_entries.iter().map(|dic_entry| dic_entry.den_homonyms.len()).sum();
+        let mut dic_text = format!("{}\n", dic_entries.len() + homonym_count);
+        let flagged_field = |flagged_word: &FlaggedWord| {
+            let mut field = CharCase::restore_case(flagged_word.flw_char_case, &flagged_word.flw_word);
+            if !flagged_word.flw_flags.is_empty() {
+                field += "/";
+                field += &self.join_flags(&flagged_word.flw_flags);
+            }
+            field
+        };
+        for dic_entry in dic_entries {
+            let mut fields: Vec<String> = dic_entry.den_words.iter().map(flagged_field).collect();
+            fields.extend(dic_entry.den_morph.iter().cloned());
+            dic_text += &fields.join(" ");
+            dic_text += "\n";
+            for (flagged_word, morph) in dic_entry.den_homonyms.iter().zip(&dic_entry.den_homonym_morph) {
+                let mut fields = vec![flagged_field(flagged_word)];
+                fields.extend(morph.iter().cloned());
+                dic_text += &fields.join(" ");
+                dic_text += "\n";
+            }
+        }
+        dic_text
+    }
+
+    /// Returns a value of slg_generation not used before in the process, never 0,
+    /// so that WordCache doesn't mix the results of two dictionaries.
+    pub fn next_generation() -> u32 {
+        static GENERATIONS: AtomicU32 = AtomicU32::new(1);
+        let generation = GENERATIONS.fetch_add(1, Ordering::Relaxed);
+        if generation == 0 {
+            // wrapped around
+            GENERATIONS.fetch_add(1, Ordering::Relaxed)
+        } else {
+            generation
+        }
+    }
+
+    /// Enables the features combined in 'features', see CheckFeature.
+    /// The cached results of WordCache are not used after the change.
+    pub fn set_features(&mut self, features: u32) {
+        self.slg_features = features;
+        self.slg_generation = Self::next_generation();
+    }
+
+    /// Adds the word without flags to the loaded dictionary, e.g. from a user's word list.
+    /// A word of the dictionary keeps its entry with the flags and the morphological fields,
+    /// the word in another case is added to it as a homonym, e.g. "Polish" to "polish".
+    /// The cached results of WordCache are not used after the change.
+    pub fn add_word(&mut self, word: &str) {
+        let mut dic_entry = DicEntry::new(0, word.to_string());
+        dic_entry.den_words.push(FlaggedWord::new(word, vec![]));
+        let key = dic_entry.hash_key();
+        if let Some(existing_entry) = self.slg_dic_hash.get_mut(key.as_str()) {
+            let flagged_word = dic_entry.den_words.remove(0);
+            if existing_entry.den_words.len() == 1
+                && existing_entry
+                    .flagged_words()
+                    .all(|existing_word| existing_word.flw_char_case != flagged_word.flw_char_case)
+            {
+                existing_entry.den_homonyms.push(flagged_word);
+                existing_entry.den_homonym_morph.push(vec![]);
+                self.slg_generation = Self::next_generation();
+            }
+            return;
+        }
+        let key: Arc<str> = key.into();
+        if let Err(index_pos) = self.slg_word_index.binary_search(&key) {
+            self.slg_word_index.insert(index_pos, key.clone());
+            self.slg_ngram_index.add_key(&key);
+        }
+        let lowercase = key.to_lowercase();
+        if *lowercase != *key {
+            self.slg_mixed_case_keys.entry(lowercase).or_default().push(key.clone());
+        }
+        if !self.slg_phone.is_empty() {
+            let phone_code = Spell::phonetic_code(self, &key);
+            self.slg_phone_codes.entry(phone_code).or_default().push(key.clone());
+        }
+        self.slg_dic_hash.insert(key, dic_entry);
+        self.slg_generation = Self::next_generation();
+    }
+
+    /// The built-in keyboard layouts: the name and the rows of keys, in the format of KEY.
+    pub const KEYBOARD_LAYOUTS: [(&'static str, &'static str); 4] = [
+        ("qwerty", "qwertyuiop|asdfghjkl|zxcvbnm"),
+        ("qwertz", "qwertzuiop|asdfghjkl|yxcvbnm"),
+        ("azerty", "azertyuiop|qsdfghjklm|wxcvbn"),
+        ("dvorak", "pyfgcrl|aoeuidhtns|qjkxbmwvz"),
+    ];
+
+    /// Returns the keyboard layout for the suggestions: the built-in layout selected
+    /// by slg_keyboard, otherwise KEY of the dictionary, otherwise qwerty.
+    pub fn keyboard_layout(&self) -> &str {
+        if let Some((_, layout)) = Self::KEYBOARD_LAYOUTS.iter().find(|(name, _)| *name == self.slg_keyboard) {
+            return layout;
+        }
+        if !self.slg_key.is_empty() {
+            return &self.slg_key;
+        }
+        Self::KEYBOARD_LAYOUTS[0].1
+    }
+
+    /// The largest number of characters substituted or inserted in the suggestions without TRY,
+    /// the rare characters of e.g. a CJK dictionary would multiply the candidates.
+    pub const WORD_CHARS_MAX: usize = 64;
+
+    /// Sorts the dictionary keys for the prefix search in completions, groups the mixed-case ones
+    /// by their lowercase for the suggestions,
+    /// counts their characters for the suggestions without TRY, indexes them by their n-grams
+    /// and with the PHONE table, groups them by their phonetic code.
+    /// Called after loading the dictionary words. The indexes share the keys of slg_dic_hash.
+    pub fn index_words(&mut self) {
+        self.slg_word_index = self
+            .slg_dic_hash
+            .iter()
+            .filter(|(_, dic_entry)| dic_entry.den_words.len() == 1)
+            .map(|(key, _)| key.clone())
+            .collect();
+        self.slg_word_index.sort_unstable();
+        self.slg_ngram_index = NgramIndex::default();
+        for key in &self.slg_word_index {
+            self.slg_ngram_index.add_key(key);
+        }
+        self.slg_mixed_case_keys.clear();
+        for key in &self.slg_word_index {
+            let lowercase = key.to_lowercase();
+            if *lowercase != **key {
+                self.slg_mixed_case_keys.entry(lowercase).or_default().push(key.clone());
+            }
+        }
+        self.slg_phone_codes.clear();
+        if !self.slg_phone.is_empty() {
+            let phone_rules = self.phone_rules();
+            for key in &self.slg_word_index {
+                let phone_code = Spell::phonetic_code_by_rules(&phone_rules, key);
+                self.slg_phone_codes.entry(phone_code).or_default().push(key.clone());
+            }
+        }
+        let mut char_counts: HashMap<char, u32> = HashMap::default();
+        for key in &self.slg_word_index {
+            for c in key.chars() {
+                *char_counts.entry(c).or_insert(0) += 1;
+            }
+        }
+        let mut word_chars: Vec<(char, u32)> = char_counts.into_iter().collect();
+        word_chars.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        self.slg_word_chars = word_chars.into_iter().take(Self::WORD_CHARS_MAX).map(|(c, _)| c).collect();
+        self.slg_generation = Self::next_generation();
+    }
+
+    /// Returns the parsed rules of the PHONE table, in its order.
+    pub fn phone_rules(&self) -> Vec<PhoneRule> {
+        self.slg_phone
+            .iter()
+            .map(|(pattern, replacement)| Parser::parse_phone_rule(pattern, replacement))
+            .collect()
+    }
+
+    /// Returns the byte positions in the word where it can be hyphenated, by the patterns of NEA HYPH
+    /// (Liang's patterns, as in the hyphenation dictionaries of TeX and LibreOffice),
+    /// e.g. [2, 6] for "hyphenation" (hy-phen-ation). The odd levels of the matching patterns
+    /// allow a break, the even ones forbid it, the highest level wins.
+    /// The "." in the patterns matches the start or the end of the word.
+    /// A break leaves at least one character on each side. Empty without NEA HYPH.
+    pub fn hyphenate(&self, word: &str) -> Vec<usize> {
+        if self.slg_hyph_patterns.is_empty() {
+            return vec![];
+        }
+        let mut letters: Vec<char> = vec!['.'];
+        letters.extend(word.chars().map(|c| c.to_lowercase().next().unwrap_or(c)));
+        letters.push('.');
+        let mut levels: Vec<u8> = vec![0; letters.len() + 1];
+        let mut key = String::new();
+        for start in 0..letters.len() {
+            key.clear();
+            for &letter in &letters[start..] {
+                key.push(letter);
+                if let Some(pattern_levels) = self.slg_hyph_patterns.get(&key) {
+                    for (ix, &level) in pattern_levels.iter().enumerate() {
+                        levels[start + ix] = levels[start + ix].max(level);
+                    }
+                }
+            }
+        }
+        // the break before the character at char_ix of the word is at levels[char_ix + 1]
+        word.char_indices()
+            .enumerate()
+            .skip(1)
+            .filter(|(char_ix, _)| levels[char_ix + 1] % 2 == 1)
+            .map(|(_, (pos, _))| pos)
+            .collect()
+    }
+
+    /// Returns the number of syllables of the word: with HYPH, one more than its hyphenation
+    /// points (see hyphenate), or else the number of the vowels of COMPOUNDSYLLABLE in it,
+    /// as in Hunspell. Without both tags, 0.
+    pub fn syllable_count(&self, word: &str) -> usize {
+        if !self.slg_hyph_patterns.is_empty() {
+            self.hyphenate(word).len() + 1
+        } else {
+            word.chars()
+                .filter(|c| self.slg_comp_syllable.1.contains(c.to_lowercase().next().unwrap_or(*c)))
+                .count()
+        }
+    }
+
+    /// Returns true if the feature is enabled, see slg_features.
+    pub fn has_feature(&self, feature: CheckFeature) -> bool {
+        (self.slg_features & feature as u32) != 0
+    }
+
+    /// Returns the TRY string, the characters of the language ordered by their frequency,
+    /// e.g. for an on-screen keyboard. Empty without TRY.
+    pub fn try_chars(&self) -> &str {
+        &self.slg_try
+    }
+
+    /// Returns the characters substituted or inserted in the suggestions:
+    /// the TRY string in its order, or without it, the characters of the dictionary words.
+    pub fn suggest_alphabet(&self) -> Vec<char> {
+        if self.slg_try.is_empty() {
+            return self.slg_word_chars.clone();
+        }
+        let mut alphabet: Vec<char> = vec![];
+        for c in self.slg_try.chars() {
+            if !alphabet.contains(&c) {
+                alphabet.push(c);
+            }
+        }
+        alphabet
+    }
+
+    /// Returns at most 'limit' dictionary words starting with the prefix, in the sorted order
+    /// of their keys, with their dictionary case, e.g. "London" for "lon".
+    /// The homonyms differing in case follow their word, e.g. "polish" and "Polish".
+    pub fn completions(&self, prefix: &str, limit: usize) -> Vec<String> {
+        let (_, key_prefix) = CharCase::normalize_case(prefix);
+        let start = self.slg_word_index.partition_point(|key| **key < *key_prefix);
+        self.slg_word_index[start..]
+            .iter()
+            .take_while(|key| key.starts_with(&key_prefix))
+            .flat_map(|key| {
+                self.slg_dic_hash[key]
+                    .flagged_words()
+                    .map(move |flagged_word| CharCase::restore_case(flagged_word.flw_char_case, key))
+            })
+            .take(limit)
+            .collect()
+    }
+
+    /// Returns the stems of the single-word dictionary entries, in no particular order.
+    /// The stems are case-normalized, as they are stored in the dictionary.
+    pub fn stems(&self) -> impl Iterator<Item = &str> {
+        self.slg_dic_hash
+            .iter()
+            .filter(|(_, dic_entry)| dic_entry.den_words.len() == 1)
+            .map(|(key, _)| &**key)
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TokenType {
+    NotWord,
+    IsWord,
+    IsGoodWord, // spelling-check passed
+    IsBadWord, // spelling-check failed
+}
+
+/// The class of a character, see Tokenizer::char_class.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum CharClass {
+    Letter,
+    /// a character of WORDCHARS or IGNORE, in a word or not, e.g. the apostrophe
+    WordCharOptional,
+    /// an ascii digit, when WORDCHARS include all the digits
+    Digit,
+    NonWord,
+}
+
+/// Splits the text into words and other tokens, by the rules for the word characters.
+/// It can be used without a dictionary, see Tokenizer::new, or with the rules
+/// of a dictionary, see Tokenizer::from_lang.
+#[derive(Clone, Copy)]
+pub struct Tokenizer<'a> {
+    /// all the ascii digits can be in words
+    pub tkn_wordchar_digits: bool,
+    /// non-alphabetic characters that can be in words, e.g. dot, hyphen or apostrophe
+    pub tkn_wordchars: &'a [char],
+    /// characters that don't split words and are ignored when checking, e.g. soft hyphen
+    pub tkn_ignore: &'a str,
+    /// combined ModeFlag values, e.g. ModeFlag::Identifiers
+    pub tkn_mode_flags: u32,
+    /// prefixes and suffixes split from the words, e.g. "USB-" in "USB-enabled"
+    pub tkn_soft_prefixes: &'a [String],
+    pub tkn_soft_suffixes: &'a [String],
+    /// the longer tokens are not words to check, see slg_word_len_max
+    pub tkn_word_len_max: usize,
+    /// the tokens matching any of the patterns are not words to check, see slg_skip_patterns
+    pub tkn_skip_patterns: &'a [SkipPattern],
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Tokenizer with the alphabetic characters and 'wordchars' in words.
+    pub fn new(wordchars: &'a [char]) -> Tokenizer<'a> {
+        Tokenizer {
+            tkn_wordchar_digits: false,
+            tkn_wordchars: wordchars,
+            tkn_ignore: "",
+            tkn_mode_flags: 0,
+            tkn_soft_prefixes: &[],
+            tkn_soft_suffixes: &[],
+            tkn_word_len_max: usize::MAX,
+            tkn_skip_patterns: &[],
+        }
+    }
+
+    /// Tokenizer with the rules of the dictionary: WORDCHARS, IGNORE and the mode flags.
+    /// The soft prefixes and suffixes are split only with CheckFeature::SoftSplit.
+    pub fn from_lang(spell_lang: &'a SpellLang) -> Tokenizer<'a> {
+        let soft_splitting = spell_lang.has_feature(CheckFeature::SoftSplit);
+        Tokenizer {
+            tkn_wordchar_digits: spell_lang.slg_wordchar_digits,
+            tkn_wordchars: &spell_lang.slg_wordchars,
+            tkn_ignore: &spell_lang.slg_ignore,
+            tkn_mode_flags: spell_lang.slg_mode_flags,
+            tkn_soft_prefixes: if soft_splitting { &spell_lang.slg_soft_prefixes } else { &[] },
+            tkn_soft_suffixes: if soft_splitting { &spell_lang.slg_soft_suffixes } else { &[] },
+            tkn_word_len_max: spell_lang.slg_word_len_max,
+            tkn_skip_patterns: &spell_lang.slg_skip_patterns,
+        }
+    }
+
+    /// Returns true if the (non-alphabetic) character can be either in a word or not.
+    /// There are two spaces in example 'It's five o'clock.' so three token are produced.
+    /// In the first token ('It's), the first apostrophe is not part of word,
+    /// the second one is part of word.
+    pub fn is_non_alphabetic_in_word(&self, c: char) -> bool {
+        let c = if (self.tkn_mode_flags & ModeFlag::NormalizeQuotes as u32) != 0 {
+            Spell::normalize_quote(c)
+        } else {
+            c
+        };
+        self.tkn_wordchar_digits && c.is_ascii_digit()
+            || self.tkn_wordchars.contains(&c)
+            || c == '_' && (self.tkn_mode_flags & ModeFlag::Identifiers as u32) != 0
+    }
+
+    /// Returns the class of the character by the rules for the words.
+    pub fn char_class(&self, c: char) -> CharClass {
+        if c.is_alphabetic() {
+            CharClass::Letter
+        } else if self.tkn_wordchar_digits && c.is_ascii_digit() {
+            CharClass::Digit
+        } else if self.is_non_alphabetic_in_word(c) || self.tkn_ignore.contains(c) {
+            CharClass::WordCharOptional
+        } else {
+            CharClass::NonWord
+        }
+    }
+
+    // Returns true if the character can be in a word.
+    // The characters of IGNORE tag, e.g. soft hyphen, don't split words.
+    fn in_word_or_optional(&self, c: char) -> bool {
+        c.is_alphabetic() || self.is_non_alphabetic_in_word(c) || self.tkn_ignore.contains(c)
+    }
+
+    /// Returns the type of a token of characters that can be in a word.
+    /// Tokens without letters, e.g. "---" or "..." or "2024", are not words,
+    /// nor the tokens longer than tkn_word_len_max characters or matching a skip pattern.
+    fn word_token_type(&self, token: &str) -> TokenType {
+        if token.chars().any(|c| c.is_alphabetic())
+            && token.chars().nth(self.tkn_word_len_max).is_none()
+            && !self.tkn_skip_patterns.iter().any(|pattern| pattern.match_token(token))
+        {
+            TokenType::IsWord
+        } else {
+            TokenType::NotWord
+        }
+    }
+
+    /// Pushes the span of a word token, after splitting the soft prefix and the soft suffix
+    /// (as tokens that are not words) from it, e.g. "USB-" and "enabled" from "USB-enabled".
+    fn push_word_spans(&self, untokenized_text: &str, span: Range<usize>, span_vec: &mut Vec<(Range<usize>, TokenType)>) {
+        let token = &untokenized_text[span.clone()];
+        let soft_len = |soft_breaks: &[String], matches: &dyn Fn(&str) -> bool| {
+            soft_breaks
+                .iter()
+                .filter(|soft_break| soft_break.len() < token.len() && matches(soft_break))
+                .map(|soft_break| soft_break.len())
+                .max()
+                .unwrap_or(0)
+        };
+        let prefix_len = soft_len(self.tkn_soft_prefixes, &|soft_break| token.starts_with(soft_break));
+        let suffix_len = soft_len(self.tkn_soft_suffixes, &|soft_break| token.ends_with(soft_break));
+        let (start_ix, mut end_ix) = (span.start + prefix_len, span.end);
+        if prefix_len > 0 {
+            span_vec.push((span.start..start_ix, TokenType::NotWord));
+        }
+        if suffix_len > 0 && start_ix + suffix_len < end_ix {
+            end_ix -= suffix_len;
+        }
+        span_vec.push((start_ix..end_ix, self.word_token_type(&untokenized_text[start_ix..end_ix])));
+        if end_ix < span.end {
+            span_vec.push((end_ix..span.end, TokenType::NotWord));
+        }
+    }
+
+    /// Splits `untokenized_text` into a vector of tuples
+    /// Vec<(byte_range_in_text: Range<usize>, token_type: TokenType)>
+    pub fn tokenize_spans(&self, untokenized_text: &str) -> Vec<(Range<usize>, TokenType)> {
+        let parts = untokenized_text.match_indices(|c: char| !self.in_word_or_optional(c));
+        let mut span_vec = Vec::<(Range<usize>, TokenType)>::new();
+        let mut last_ix: usize = 0; // end of last pushed non-word
+        for part in parts {
+            let (start_ix, word) = part;
+            if last_ix < start_ix {
+                self.push_word_spans(untokenized_text, last_ix..start_ix, &mut span_vec);
+            }
+            span_vec.push((start_ix..start_ix + word.len(), TokenType::NotWord));
+            last_ix = start_ix + word.len();
+        }
+        if last_ix < untokenized_text.len() {
+            self.push_word_spans(untokenized_text, last_ix..untokenized_text.len(), &mut span_vec);
+        }
+        span_vec
+    }
+
+    /// Changes `untokenized_text` into a vector of tuples
+    /// Vec<(a_string_of_charactes: String, token_type: TokenType)>
+    pub fn tokenize(&self, untokenized_text: &str) -> Vec<(String, TokenType)> {
+        self.tokenize_spans(untokenized_text)
+            .into_iter()
+            .map(|(span, token_type)| (untokenized_text[span].to_string(), token_type))
+            .collect()
+    }
+}
+
+/// Data about the loaded dictionary, see SpellLang::summary.
+/// Displayed as text, e.g. "encoding UTF-8, affixes 2/30, word entries 50000."
+#[derive(Clone, Debug)]
+pub struct Summary {
+    /// the SET element, e.g. "UTF-8"
+    pub smr_encoding: String,
+    /// the number of defined flags, for affixes and other purposes
+    pub smr_flag_count: usize,
+    /// the number of affix entries in all the affix classes
+    pub smr_affix_entry_count: u32,
+    /// the number of dictionary entries, without duplicates
+    pub smr_word_count: usize,
+    pub smr_duplicate_count: u32,
+    /// tags in the aff file that are not parsed, with their counts
+    pub smr_unknown_tags: HashMap<String, u32>,
+    /// flags in the dic file that are not defined, with their counts
+    pub smr_unknown_flags: HashMap<String, u32>,
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "encoding {}, affixes {}/{}, word entries {}",
+            self.smr_encoding, self.smr_flag_count, self.smr_affix_entry_count, self.smr_word_count
+        )?;
+        if self.smr_duplicate_count != 0 {
+            write!(f, ", {} duplicated entries", self.smr_duplicate_count)?;
+        }
+        for (title, counts) in [("tags", &self.smr_unknown_tags), ("flags", &self.smr_unknown_flags)] {
+            for (ix, (key, value)) in counts.iter().enumerate() {
+                let separator = if ix == 0 { format!(", other {title} ") } else { String::from(",") };
+                write!(f, "{separator}{key}*{value}")?;
+            }
+        }
+        write!(f, ".")
+    }
+}
+
+/// A reason for rejecting a word, see Spell::diagnose.
+#[derive(PartialEq, Clone, Debug)]
+pub enum RejectionReason {
+    /// neither the word nor its stem after removing affixes is in the dictionary
+    NotInDictionary,
+    /// the word is in the dictionary with another case, e.g. "Unicef" for "UNICEF"
+    CaseMismatch(CharCase),
+    /// the word has the FORBIDDENWORD flag, on the stem or on an affix
+    Forbidden,
+    /// the dictionary word has the NEEDAFFIX flag, it's only valid with an affix
+    NeedsAffix,
+    /// the dictionary word has the ONLYINCOMPOUND flag
+    OnlyInCompound,
+}
+
+/// The result of Spell::diagnose, all the reasons for rejecting a word.
+#[derive(PartialEq, Clone, Debug)]
+pub struct RejectionInfo {
+    pub rji_reasons: Vec<RejectionReason>,
+}
+
+impl RejectionInfo {
+    /// True when no reason for rejecting was found.
+    pub fn is_accepted(&self) -> bool {
+        self.rji_reasons.is_empty()
+    }
+}
+
+/// The result of Spell::check_word, the suggestions are empty for the correct word.
+#[derive(PartialEq, Clone, Debug)]
+pub struct WordResult {
+    pub wrs_is_correct: bool,
+    pub wrs_suggestions: Vec<String>,
+}
+
+/// The kind of an issue found in the text, see Spell::check_text_issues.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum IssueKind {
+    /// the word is not accepted by the dictionary
+    Misspelling,
+    /// the word repeats the previous word, e.g. "the the"
+    RepeatedWord,
+}
+
+/// An issue found in the text, with the byte range of the word in the text.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Issue {
+    pub isu_kind: IssueKind,
+    pub isu_span: Range<usize>,
+    pub isu_suggestions: Vec<String>, // the corrections of the misspelled word
+}
+
+/// The results of Spell::check_token for the words already checked, for the texts
+/// repeating the words. The cache is cleared when the dictionary is changed,
+/// recognized by its slg_generation. The changes by add_word and set_features renew it,
+/// the caller changing the pub fields of SpellLang directly must renew it, e.g.
+/// `spell_lang.slg_generation = SpellLang::next_generation();`, or the stale results are used.
+/// See benches/word_cache.rs for the gain on a repetitive text.
+pub struct WordCache {
+    wch_results: HashMap<String, bool>,
+    /// the value of slg_generation of the dictionary when the results were cached, 0 before
+    wch_generation: u32,
+    /// with so many words, the cache is cleared before adding more
+    wch_max_words: usize,
+}
+
+impl WordCache {
+    pub fn new(wch_max_words: usize) -> WordCache {
+        WordCache {
+            wch_results: HashMap::default(),
+            wch_generation: 0,
+            wch_max_words,
+        }
+    }
+
+    /// Checks the word like Spell::check_token, using the cached result if present.
+    /// The cache is cleared when used with another dictionary.
+    pub fn check_token(&mut self, spell_lang: &SpellLang, word: &str) -> bool {
+        if self.wch_generation != spell_lang.slg_generation {
+            self.wch_results.clear();
+            self.wch_generation = spell_lang.slg_generation;
+        }
+        if let Some(result) = self.wch_results.get(word) {
+            return *result;
+        }
+        let result = Spell::check_token(spell_lang, word);
+        if self.wch_results.len() >= self.wch_max_words {
+            self.wch_results.clear();
+        }
+        self.wch_results.insert(word.to_string(), result);
+        result
+    }
+
+    /// Returns the number of cached words.
+    pub fn len(&self) -> usize {
+        self.wch_results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wch_results.is_empty()
+    }
+}
+
+/// The affixes already removed from the original word in Spell::check_decased_word.
+#[derive(Default)]
+struct AffixStrip {
+    afs_removed_ixes: Vec<u32>,      // the groups of the removed affixes
+    afs_circumfix_pending: bool,     // true when one half of a CIRCUMFIX pair has been removed
+    afs_accepted_ixes: Vec<u32>,     // the groups of the affixes of the accepted word
+    afs_inner_prefix: bool,          // the word is a compound part after another part
+    afs_inner_suffix: bool,          // the word is a compound part before another part
+}
+
+/// The parts already removed from the start of the word in Spell::check_compound.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+struct CompoundPrev<'w> {
+    cpv_parts: usize,          // the number of the removed parts
+    cpv_syllables: usize,      // their syllables, counted for COMPOUNDSYLLABLE
+    cpv_last: Option<&'w str>, // the last removed part
+}
+
+/// The state of the search in Spell::check_compound_rules: the length of the rest of the word,
+/// the count, the syllables and the last of the previous parts, and the positions in the rules.
+type CompoundRuleState<'w> = (usize, usize, usize, Option<&'w str>, Vec<Vec<bool>>);
+
+/// Counts the affix classes used by the accepted words, to find the classes
+/// never used in a corpus, e.g. when slimming a dictionary.
+/// The affixes of the compound parts are not counted.
+pub struct AffixUsage {
+    /// the count of the accepted words for each affix class, by the index in slg_aff_groups
+    afu_hits: Vec<u32>,
+}
+
+impl AffixUsage {
+    pub fn new(spell_lang: &SpellLang) -> AffixUsage {
+        AffixUsage { afu_hits: vec![0; spell_lang.slg_aff_groups.len()] }
+    }
+
+    /// Checks the word like Spell::check_token and counts the affix classes of the accepted word.
+    pub fn check_token(&mut self, spell_lang: &SpellLang, word: &str) -> bool {
+        let mut affix_strip = AffixStrip::default();
+        if Spell::check_strip_flags(spell_lang, word, &mut affix_strip).is_none() {
+            return false;
+        }
+        for ix in affix_strip.afs_accepted_ixes {
+            if let Some(hits) = self.afu_hits.get_mut(ix as usize) {
+                *hits += 1;
+            }
+        }
+        true
+    }
+
+    /// Returns the names of the affix classes without any accepted word, in the order of the affix file.
+    pub fn unused_classes<'s>(&self, spell_lang: &'s SpellLang) -> Vec<&'s str> {
+        spell_lang
+            .slg_aff_groups
+            .iter()
+            .filter(|affix_group| self.afu_hits.get(affix_group.afc_ix as usize).copied().unwrap_or(0) == 0)
+            .map(|affix_group| affix_group.afc_name.as_str())
+            .collect()
+    }
+}
+
+/// Functions for spelling words and suggesting corrections.
+pub struct Spell {}
+
+impl Spell {
+    /// The punctuation ending a sentence.
+    pub const SENTENCE_ENDS: &'static str = ".!?";
+
+    /// The function returns true if the word is present in the dictionary
+    /// and (optionally) if it has the required flag, see present_flagged_word.
+    fn word_present(
+        spell_lang: &SpellLang,
+        char_case: CharCase,
+        word: &str,
+        flag: Option<&String>,
+        in_compound: bool,
+    ) -> bool {
+        Spell::present_flagged_word(spell_lang, char_case, word, flag, in_compound).is_some()
+    }
+
+    /// Returns the dictionary word present in the case and (optionally) having the required flag.
+    /// Of the homonyms differing in case, e.g. "polish" and "Polish", the word of the same case
+    /// is tried first.
+    /// The words with ONLYINCOMPOUND flag are present only 'in_compound'.
+    /// The words with KEEPCASE flag are present only in the case of the dictionary.
+    /// todo: process multi-word entries
+    fn present_flagged_word<'s>(
+        spell_lang: &'s SpellLang,
+        char_case: CharCase,
+        word: &str,
+        flag: Option<&String>,
+        in_compound: bool,
+    ) -> Option<&'s FlaggedWord> {
+        let dict_entry = spell_lang.slg_dic_hash.get(word)?;
+        let same_case = dict_entry.flagged_words().filter(|flagged_word| flagged_word.flw_char_case == char_case);
+        let other_case = dict_entry.flagged_words().filter(|flagged_word| flagged_word.flw_char_case != char_case);
+        same_case
+            .chain(other_case)
+            .find(|flagged_word| Spell::flagged_word_present(spell_lang, char_case, flagged_word, flag, in_compound))
+    }
+
+    /// The checks of present_flagged_word for one dictionary word.
+    fn flagged_word_present(
+        spell_lang: &SpellLang,
+        char_case: CharCase,
+        flagged_word: &FlaggedWord,
+        flag: Option<&String>,
+        in_compound: bool,
+    ) -> bool {
+        if !in_compound {
+            if let Some(only_flag) = spell_lang.special_flag(FlagType::FlagOnlyComp) {
+                if spell_lang.word_has_flag(flagged_word, only_flag) {
+                    return false;
+                }
+            }
+        }
+        let dict_case = flagged_word.flw_char_case;
+        if let Some(keep_flag) = spell_lang.special_flag(FlagType::FlagKeepCase) {
+            if char_case != dict_case && spell_lang.word_has_flag(flagged_word, keep_flag) {
+                // with CharCase::Other, e.g. 's-Gravenhage, the key is only found in the same case
+                return false;
+            }
+        }
+        if dict_case == CharCase::Upper {
+            if char_case == CharCase::Initial {
+                // the uppercase abbreviations (in dictionary) are not allowed with initial case (in text)
+                // todo define Modeflag value to allow in identifiers in programming languages like ParseHtml
+                return false;
+            }
+        }
+        if dict_case == CharCase::Upper || dict_case == CharCase::Initial {
+            if (spell_lang.slg_mode_flags as u32 & ModeFlag::TestCompat as u32) != 0
+                && char_case == CharCase::Lower
+            {
+                //mail addresses and other internet identificators are lowercase
+                // such lowercase is not allowed in ModeFlag::TestCompat
+                return false;
+            }
+        }
+        if let Some(flag) = flag {
+            return flagged_word.flw_flags.contains(flag);
+        }
+        true // no flags to check
+    }
+
+    /// Returns true if 'substring' is at the start or at the end of 'word',
+    /// depending on 'is_prefix'.
+    fn is_substring_at_edge(word: &str, substring: &str, is_prefix: bool) -> bool {
+        if is_prefix {
+            word.starts_with(substring)
+        } else {
+            word.ends_with(substring)
+        }
+    }
+
+    /// Returns true if the dictionary word has the NEEDAFFIX flag,
+    /// it's accepted only after removing an affix.
+    fn needs_affix(spell_lang: &SpellLang, flagged_word: &FlaggedWord) -> bool {
+        spell_lang
+            .special_flag(FlagType::FlagNeedAffix)
+            .is_some_and(|need_affix_flag| spell_lang.word_has_flag(flagged_word, need_affix_flag))
+    }
+
+    /// Adds the flags of the dictionary word (stem) to 'found_flags'.
+    fn add_stem_flags(spell_lang: &SpellLang, word: &str, found_flags: &mut Vec<String>) {
+        if let Some(dict_entry) = spell_lang.slg_dic_hash.get(word) {
+            found_flags.extend_from_slice(&dict_entry.den_words[0].flw_flags);
+        }
+    }
+
+    /// The function returns true if the word is correctly spelled in spell_lang
+    /// and (for languages with uppercase and lowercase letters)
+    /// has the character case as in the dictionary.
+    /// Thus far, the affixes of the groups in affix_strip have already been removed from the original word.
+    /// These groups are not removed again, also when the continuation flags form a cycle.
+    /// For the second affix of the same place, only affix groups in ix_subset are allowed.
+    /// The affixes with CIRCUMFIX are accepted only in pairs, e.g. the prefix with the suffix.
+    fn check_decased_word(
+        spell_lang: &SpellLang,
+        mut char_case: CharCase,
+        word: &str,
+        ix_subset: Option<&Vec<u32>>,
+        found_flags: &mut Vec<String>, // for the accepted word, the flags of its stem and affixes
+        affix_strip: &mut AffixStrip,
+        in_compound: bool, // the word is a part of compound, see ONLYINCOMPOUND
+    ) -> bool {
+        // so many prefixes and suffixes has been processed
+        let prefix_ct = affix_strip
+            .afs_removed_ixes
+            .iter()
+            .filter(|ix| spell_lang.slg_aff_groups[**ix as usize].afc_is_pre)
+            .count() as u32;
+        let suffix_ct = affix_strip.afs_removed_ixes.len() as u32 - prefix_ct;
+        let affixed = !affix_strip.afs_removed_ixes.is_empty();
+        let circumfix_flag = spell_lang.special_flag(FlagType::FlagCircumfix);
+        let stem_word = Spell::present_flagged_word(spell_lang, char_case, word, None, in_compound);
+        if let Some(stem_word) = stem_word.filter(|stem_word| {
+            ix_subset == None
+                && (affixed || !Spell::needs_affix(spell_lang, stem_word))
+                && !affix_strip.afs_circumfix_pending
+        }) {
+            found_flags.extend_from_slice(&stem_word.flw_flags);
+            affix_strip.afs_accepted_ixes = affix_strip.afs_removed_ixes.clone();
+            return true;
+        }
+        let mut base_word = String::with_capacity(128); // not to allocate it often, it's defined here
+                                                        // after removing affix from a word with other casing, the casing of the new word can be different
+        let originally_other_case = char_case == CharCase::Other;
+        for affix_group in &spell_lang.slg_aff_groups {
+            if affix_strip.afs_removed_ixes.contains(&affix_group.afc_ix) {
+                continue;
+            }
+            let new_prefix_ct = if affix_group.afc_is_pre {
+                prefix_ct + 1
+            } else {
+                prefix_ct
+            };
+            let new_suffix_ct = if affix_group.afc_is_pre {
+                suffix_ct
+            } else {
+                suffix_ct + 1
+            };
+            // new_prefix_ct and new_suffix_ct are the counts after applying any affix_entry from affix_group
+            if new_prefix_ct > spell_lang.slg_prefix_max
+                || new_suffix_ct > spell_lang.slg_suffix_max
+            {
+                continue; // this would be too many levels for prefixes or suffixes
+            }
+            let same_place_ct = if affix_group.afc_is_pre {
+                new_prefix_ct
+            } else {
+                new_suffix_ct
+            };
+            if same_place_ct >= 2 {
+                if !spell_lang.has_feature(CheckFeature::AffixContinuation) {
+                    continue; // disabled by option --features
+                }
+                // when applying the second (or further) affix of the same place, only some affixes are allowed
+                if let Some(subset) = ix_subset {
+                    if !subset.contains(&affix_group.afc_ix) {
+                        continue; // skip such affix group, not in a vector of required indexes
+                    }
+                }
+            }
+            for affix_entry in &affix_group.afc_affixes {
+                if !Spell::is_substring_at_edge(word, &affix_entry.afe_add, affix_group.afc_is_pre)
+                {
+                    continue;
+                }
+                // the part of the word that remains after removing the affix
+                let Some(remaining_len) = word.len().checked_sub(affix_entry.afe_add.len()) else {
+                    continue;
+                };
+                if remaining_len == 0 && !spell_lang.slg_full_string {
+                    continue; // only with FULLSTRIP, the affix can replace the whole word
+                }
+                // from word to base_word: -add, +sub
+                base_word.clear();
+                if affix_group.afc_is_pre {
+                    base_word += &affix_entry.afe_sub;
+                    base_word += &word[affix_entry.afe_add.len()..];
+                } else {
+                    base_word += &word[..word.len() - affix_entry.afe_add.len()];
+                    base_word += &affix_entry.afe_sub;
+                }
+                if originally_other_case {
+                    (char_case, base_word) = CharCase::normalize_case(&base_word);
+                }
+                // now check the base_word
+                if !affix_entry
+                    .afe_cond
+                    .match_edge(&base_word, affix_group.afc_is_pre)
+                {
+                    continue;
+                }
+                if in_compound && !Spell::affix_fits_compound(spell_lang, affix_entry, affix_group.afc_is_pre, affix_strip) {
+                    continue;
+                }
+                let was_pending = affix_strip.afs_circumfix_pending;
+                let circumfix_pending = was_pending
+                    != circumfix_flag.is_some_and(|flag| spell_lang.flags_contain(&affix_entry.afe_next_flags, flag));
+                let stem_word = Spell::present_flagged_word(
+                    spell_lang,
+                    char_case,
+                    &base_word,
+                    Some(&affix_group.afc_name),
+                    in_compound,
+                );
+                if let Some(stem_word) = stem_word.filter(|_| !circumfix_pending) {
+                    found_flags.extend_from_slice(&stem_word.flw_flags);
+                    found_flags.extend_from_slice(&affix_entry.afe_next_flags);
+                    affix_strip.afs_accepted_ixes = affix_strip.afs_removed_ixes.clone();
+                    affix_strip.afs_accepted_ixes.push(affix_group.afc_ix);
+                    return true;
+                }
+                affix_strip.afs_removed_ixes.push(affix_group.afc_ix);
+                affix_strip.afs_circumfix_pending = circumfix_pending;
+                let accepted = Spell::check_decased_word(
+                    spell_lang,
+                    char_case,
+                    &base_word,
+                    Some(&affix_group.afc_prev_flags),
+                    found_flags,
+                    affix_strip,
+                    in_compound,
+                );
+                affix_strip.afs_removed_ixes.pop();
+                affix_strip.afs_circumfix_pending = was_pending;
+                if accepted {
+                    found_flags.extend_from_slice(&affix_entry.afe_next_flags);
+                    return true;
+                }
+            }
+        }
+        // lng_mode_flags
+        false
+    }
+
+    /// Returns true if the affix is allowed on the compound part: not with COMPOUNDFORBIDFLAG,
+    /// and inside the compound (the prefix after another part or the suffix before another part)
+    /// only with COMPOUNDPERMITFLAG.
+    fn affix_fits_compound(spell_lang: &SpellLang, affix_entry: &AffixEntry, is_prefix: bool, affix_strip: &AffixStrip) -> bool {
+        let has_flag = |flag_type: FlagType| {
+            spell_lang
+                .special_flag(flag_type)
+                .is_some_and(|flag| spell_lang.flags_contain(&affix_entry.afe_next_flags, flag))
+        };
+        if has_flag(FlagType::FlagCompForbid) {
+            return false;
+        }
+        let is_inner = if is_prefix {affix_strip.afs_inner_prefix} else {affix_strip.afs_inner_suffix};
+        !is_inner || has_flag(FlagType::FlagCompPermit)
+    }
+
+    /// Returns true if the compound part is accepted, directly or after removing affixes,
+    /// decased when the whole word has mixed case,
+    /// and its stem has one of the flags of its position (see compound_position_flags).
+    /// A prefix is allowed only on the first part and a suffix only on the last part,
+    /// unless the affix has COMPOUNDPERMITFLAG. The affix with COMPOUNDFORBIDFLAG is not allowed.
+    fn check_compound_part(
+        spell_lang: &SpellLang,
+        char_case: CharCase,
+        part: &str,
+        is_first: bool,
+        is_last: bool,
+        found_flags: &mut Vec<String>,
+    ) -> bool {
+        let position_flags = spell_lang.compound_position_flags(is_first, is_last);
+        let decased_part: String;
+        let (char_case, part) = if char_case == CharCase::Other {
+            let part_case;
+            (part_case, decased_part) = CharCase::normalize_case(part);
+            (part_case, decased_part.as_str())
+        } else {
+            (char_case, part)
+        };
+        let mut part_flags: Vec<String> = vec![];
+        let mut affix_strip = AffixStrip {
+            afs_inner_prefix: !is_first,
+            afs_inner_suffix: !is_last,
+            ..AffixStrip::default()
+        };
+        if !Spell::check_decased_word(spell_lang, char_case, part, None, &mut part_flags, &mut affix_strip, true)
+            || !position_flags.iter().any(|flag| spell_lang.flags_contain(&part_flags, flag))
+        {
+            return false;
+        }
+        found_flags.append(&mut part_flags);
+        true
+    }
+
+    /// Returns true if the sequence of dictionary words matches the compound rule,
+    /// each word by one of its flags. The rule is matched as a regular expression,
+    /// keeping the set of the possible positions in the rule.
+    pub fn match_compound_rule(spell_lang: &SpellLang, rule: &[CompoundRuleItem], parts: &[&str]) -> bool {
+        let mut states = Spell::rule_start_states(rule);
+        for part in parts {
+            states = Spell::rule_next_states(spell_lang, rule, &states, part);
+            if !states.contains(&true) {
+                return false;
+            }
+        }
+        states[rule.len()]
+    }
+
+    /// Returns the positions in the compound rule reachable before the first word.
+    fn rule_start_states(rule: &[CompoundRuleItem]) -> Vec<bool> {
+        let mut states = vec![false; rule.len() + 1];
+        states[0] = true;
+        Spell::close_rule_states(rule, &mut states);
+        states
+    }
+
+    /// Returns the positions in the compound rule reachable from 'states' by the dictionary word 'part'.
+    fn rule_next_states(spell_lang: &SpellLang, rule: &[CompoundRuleItem], states: &[bool], part: &str) -> Vec<bool> {
+        let mut next_states = vec![false; rule.len() + 1];
+        let Some(dict_entry) = spell_lang.slg_dic_hash.get(part) else {
+            return next_states;
+        };
+        for (ix, (flag, quantifier)) in rule.iter().enumerate() {
+            if states[ix]
+                && dict_entry.flagged_words().any(|flagged_word| spell_lang.word_has_flag(flagged_word, flag))
+            {
+                next_states[if *quantifier == '*' {ix} else {ix + 1}] = true;
+            }
+        }
+        Spell::close_rule_states(rule, &mut next_states);
+        next_states
+    }
+
+    /// Adds the positions reachable without consuming a word, after skipping optional items.
+    fn close_rule_states(rule: &[CompoundRuleItem], states: &mut [bool]) {
+        for ix in 0..rule.len() {
+            if states[ix] && rule[ix].1 != ' ' {
+                states[ix + 1] = true;
+            }
+        }
+    }
+
+    /// The function returns true if the word can be split into dictionary words
+    /// matching one of COMPOUNDRULE rules. 'parts' are the words already removed
+    /// from the start of the original word, 'rule_states' are the positions they reach
+    /// in each rule, see match_compound_rule. Like in check_compound, the failed searches
+    /// of the rest of the word are kept in 'failed_states', so each one is made once.
+    fn check_compound_rules<'w>(
+        spell_lang: &SpellLang,
+        char_case: CharCase,
+        word: &'w str,
+        parts: &mut Vec<&'w str>,
+        rule_states: &[Vec<bool>],
+        failed_states: &mut HashSet<CompoundRuleState<'w>>,
+    ) -> bool {
+        if word.is_empty() {
+            return parts.len() >= 2
+                && spell_lang
+                    .slg_compoundrule_items
+                    .iter()
+                    .zip(rule_states)
+                    .any(|(rule, states)| states[rule.len()]);
+        }
+        if rule_states.iter().all(|states| !states.contains(&true)) {
+            return false;
+        }
+        let prev_syllables: usize = parts.iter().map(|part| spell_lang.compound_syllables(part)).sum();
+        if !spell_lang.compound_fits_word_max(parts.len() + 1)
+            && !spell_lang.compound_fits_syllable_max(prev_syllables + spell_lang.compound_syllables(word))
+        {
+            return false;
+        }
+        // without COMPOUNDWORDMAX, only the count of the parts up to 2 matters
+        let part_count = if spell_lang.slg_comp_word_max == 0 {parts.len().min(2)} else {parts.len()};
+        let state = (word.len(), part_count, prev_syllables, parts.last().copied(), rule_states.to_vec());
+        if failed_states.contains(&state) {
+            return false;
+        }
+        let part_case = if parts.is_empty() || char_case == CharCase::Upper {
+            char_case
+        } else {
+            CharCase::Lower
+        };
+        let ends = word.char_indices().map(|(pos, _)| pos).skip(1).chain(std::iter::once(word.len()));
+        for pos in ends {
+            let part = &word[..pos];
+            if !spell_lang.compound_part_fits_min(part)
+                || Spell::is_compound_dup(spell_lang, parts.last().copied(), part)
+                || !Spell::word_present(spell_lang, part_case, part, None, true)
+            {
+                continue;
+            }
+            let next_states: Vec<Vec<bool>> = spell_lang
+                .slg_compoundrule_items
+                .iter()
+                .zip(rule_states)
+                .map(|(rule, states)| Spell::rule_next_states(spell_lang, rule, states, part))
+                .collect();
+            parts.push(part);
+            if Spell::check_compound_rules(spell_lang, char_case, &word[pos..], parts, &next_states, failed_states) {
+                return true;
+            }
+            parts.pop();
+        }
+        failed_states.insert(state);
+        false
+    }
+
+    /// Returns true if the last compound part has FORCEUCASE and the word is lowercase,
+    /// so the compound is accepted only capitalized.
+    fn forces_upper_case(spell_lang: &SpellLang, char_case: CharCase, last_flags: &[String]) -> bool {
+        char_case == CharCase::Lower
+            && spell_lang
+                .special_flag(FlagType::FlagForceUcase)
+                .is_some_and(|force_flag| spell_lang.flags_contain(last_flags, force_flag))
+    }
+
+    /// Returns true if the part repeats the previous part of the compound
+    /// and CHECKCOMPOUNDDUP rejects such compounds, e.g. "bookbook".
+    fn is_compound_dup(spell_lang: &SpellLang, prev_part: Option<&str>, part: &str) -> bool {
+        spell_lang.slg_comp_check_dup && prev_part == Some(part)
+    }
+
+    /// Returns true if a character next to the boundary of the compound parts is uppercase
+    /// and CHECKCOMPOUNDCASE rejects such compounds, e.g. "fooBar" (foo + bar).
+    /// The word is as written only when it has mixed case, otherwise it's already decased.
+    fn is_compound_case(spell_lang: &SpellLang, word: &str, boundary: usize) -> bool {
+        spell_lang.slg_comp_check_case
+            && (word[..boundary].chars().next_back().is_some_and(char::is_uppercase)
+                || word[boundary..].chars().next().is_some_and(char::is_uppercase))
+    }
+
+    /// Returns true if a REP replacement spanning the boundary of the compound parts
+    /// gives a dictionary word, and CHECKCOMPOUNDREP rejects such compounds,
+    /// e.g. "cattail" (cat + tail) for "catail" with "REP tt t".
+    fn is_compound_rep(spell_lang: &SpellLang, char_case: CharCase, word: &str, boundary: usize) -> bool {
+        if !spell_lang.slg_comp_check_rep {
+            return false;
+        }
+        for (from, to) in &spell_lang.slg_rep {
+            for (pos, _) in word.match_indices(from.as_str()) {
+                if pos < boundary && boundary < pos + from.len() {
+                    let replaced = format!("{}{}{}", &word[..pos], to, &word[pos + from.len()..]);
+                    if Spell::word_present(spell_lang, char_case, &replaced, None, false) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// The function returns true if the word can be split into compound parts with COMPOUNDFLAG
+    /// or with the flags of their positions, COMPOUNDBEGIN, COMPOUNDMIDDLE and COMPOUNDEND.
+    /// Each part is at least COMPOUNDMIN characters long, the number of parts is limited
+    /// by COMPOUNDWORDMAX, and a linking element (NEA COMPOUNDLINK) can follow a part.
+    /// With CHECKCOMPOUNDDUP, the neighbouring parts differ. With CHECKCOMPOUNDREP,
+    /// the REP replacements over the part boundary don't give a dictionary word.
+    /// The 'prev' parts have been already removed from the start of the original word.
+    /// The first part has the case of the whole word, the next parts are lowercase,
+    /// e.g. "Schreibtischlampe" from "Schreib", "Tisch" and "Lampe".
+    /// The rest of the word after the same previous parts is searched once, the failed
+    /// searches are kept in 'failed_states', otherwise the time grows exponentially
+    /// with the word length, e.g. for "aaaaaaaaaaaaaaaaaaaaaaab" with the stems "aaa" and "aaaa".
+    fn check_compound<'w>(
+        spell_lang: &SpellLang,
+        char_case: CharCase,
+        word: &'w str,
+        prev: CompoundPrev<'w>,
+        found_flags: &mut Vec<String>,
+        failed_states: &mut HashSet<(usize, CompoundPrev<'w>)>,
+    ) -> bool {
+        // without COMPOUNDWORDMAX, the count and the syllables of the previous parts don't matter
+        let state = if spell_lang.slg_comp_word_max == 0 {
+            CompoundPrev { cpv_parts: prev.cpv_parts.min(1), cpv_syllables: 0, ..prev }
+        } else {
+            prev
+        };
+        if failed_states.contains(&(word.len(), state)) {
+            return false;
+        }
+        let CompoundPrev { cpv_parts: prev_parts, cpv_syllables: prev_syllables, cpv_last: prev_part } = prev;
+        let part_flags_allowed = spell_lang.compound_position_flags(prev_parts == 0, false);
+        let last_flags_allowed = spell_lang.compound_position_flags(false, true);
+        if part_flags_allowed.is_empty() || last_flags_allowed.is_empty() {
+            return false;
+        }
+        // the parts of the word with mixed case, e.g. "fooBar", are decased one by one
+        let next_case = match char_case {
+            CharCase::Upper | CharCase::Other => char_case,
+            _ => CharCase::Lower,
+        };
+        let part_case = if prev_parts == 0 {char_case} else {next_case};
+        let part_count = prev_parts + 1;
+        if !spell_lang.compound_fits_word_max(part_count + 1)
+            && !spell_lang.compound_fits_syllable_max(prev_syllables + spell_lang.compound_syllables(word))
+        {
+            return false;
+        }
+        for (pos, _) in word.char_indices().skip(1) {
+            let part = &word[..pos];
+            if !spell_lang.compound_part_fits_min(part)
+                || Spell::is_compound_dup(spell_lang, prev_part, part)
+                || Spell::is_compound_rep(spell_lang, part_case, word, pos)
+                || Spell::is_compound_case(spell_lang, word, pos)
+            {
+                continue;
+            }
+            let mut part_flags: Vec<String> = vec![];
+            if !Spell::check_compound_part(spell_lang, part_case, part, prev_parts == 0, false, &mut part_flags) {
+                continue;
+            }
+            let rest = &word[pos..];
+            for rest in std::iter::once(rest).chain(spell_lang.strip_compound_linkers(rest)) {
+                let mut rest_flags: Vec<String> = vec![];
+                let rest_accepted = spell_lang.compound_part_fits_min(rest)
+                    && !Spell::is_compound_dup(spell_lang, Some(part), rest)
+                    && Spell::check_compound_part(spell_lang, next_case, rest, false, true, &mut rest_flags)
+                    && !Spell::forces_upper_case(spell_lang, char_case, &rest_flags)
+                    || Spell::check_compound(
+                        spell_lang,
+                        char_case,
+                        rest,
+                        CompoundPrev {
+                            cpv_parts: part_count,
+                            cpv_syllables: prev_syllables + spell_lang.compound_syllables(part),
+                            cpv_last: Some(part),
+                        },
+                        &mut rest_flags,
+                        failed_states,
+                    );
+                if rest_accepted {
+                    found_flags.append(&mut part_flags);
+                    found_flags.append(&mut rest_flags);
+                    return true;
+                }
+            }
+        }
+        failed_states.insert((word.len(), state));
+        false
+    }
+
+    /// Returns the ascii quote or apostrophe for the typographic one, otherwise 'c'.
+    pub fn normalize_quote(c: char) -> char {
+        match c {
+            '\u{2018}' | '\u{2019}' => '\'',
+            '\u{201C}' | '\u{201D}' => '"',
+            _ => c,
+        }
+    }
+
+    /// Applies the conversion table (ICONV or OCONV) to the word.
+    /// At each position, the longest matching pattern is replaced.
+    /// The underscore at the start or at the end of a pattern anchors it
+    /// to the start or to the end of the word, e.g. "ICONV _ʼ '".
+    pub fn convert_word(conv_table: &[(String, String)], word: &str) -> String {
+        let mut converted = String::with_capacity(word.len());
+        let mut pos = 0;
+        while pos < word.len() {
+            let rest = &word[pos..];
+            let mut best_match: Option<(usize, &str)> = None; // (pattern length, replacement)
+            for (from, to) in conv_table {
+                let mut pattern: &str = from;
+                let at_start = pattern.len() > 1 && pattern.starts_with('_');
+                if at_start {
+                    pattern = &pattern[1..];
+                }
+                let at_end = pattern.len() > 1 && pattern.ends_with('_');
+                if at_end {
+                    pattern = &pattern[..pattern.len() - 1];
+                }
+                if !rest.starts_with(pattern) {
+                    continue;
+                }
+                if at_start && pos != 0 || at_end && pattern.len() != rest.len() {
+                    continue;
+                }
+                if best_match.is_none_or(|(best_len, _)| pattern.len() > best_len) {
+                    best_match = Some((pattern.len(), to));
+                }
+            }
+            if let Some((pattern_len, to)) = best_match {
+                converted += to;
+                pos += pattern_len;
+            } else {
+                let ch = rest.chars().next().unwrap();
+                converted.push(ch);
+                pos += ch.len_utf8();
+            }
+        }
+        converted
+    }
+
+    pub fn check_token(spell_lang: &SpellLang, word: &str) -> bool {
+        Spell::check_token_flags(spell_lang, word).is_some()
+    }
+
+    /// Returns true if the word is accepted and has the flag of 'flag_type' (e.g. FlagNoSuggest
+    /// or FlagWarn) on its stem or on any of its affixes, e.g. "SFX A 0 s/! ." with "NOSUGGEST !".
+    pub fn token_has_special_flag(spell_lang: &SpellLang, word: &str, flag_type: FlagType) -> bool {
+        let Some(flag) = spell_lang.special_flag(flag_type) else {
+            return false;
+        };
+        Spell::check_token_flags(spell_lang, word)
+            .is_some_and(|found_flags| spell_lang.flags_contain(&found_flags, flag))
+    }
+
+    /// Prepares the token for the dictionary lookup: applies ICONV, normalizes the quotes
+    /// (ModeFlag::NormalizeQuotes), removes the IGNORE characters and normalizes the case.
+    fn decompose_token(spell_lang: &SpellLang, word: &str) -> (CharCase, String) {
+        let converted_word;
+        let word = if spell_lang.slg_iconv.is_empty() {
+            word
+        } else {
+            converted_word = Spell::convert_word(&spell_lang.slg_iconv, word);
+            &converted_word
+        };
+        let unquoted_word: String;
+        let word = if (spell_lang.slg_mode_flags & ModeFlag::NormalizeQuotes as u32) != 0 {
+            unquoted_word = word.chars().map(Spell::normalize_quote).collect();
+            &unquoted_word
+        } else {
+            word
+        };
+        let unignored_word: String;
+        let word = if spell_lang.slg_ignore.is_empty() {
+            word
+        } else {
+            // the characters of IGNORE tag are removed before checking
+            unignored_word = word.chars().filter(|c| !spell_lang.slg_ignore.contains(*c)).collect();
+            &unignored_word
+        };
+        CharCase::normalize_case(word)
+    }
+
+    /// Checks the word like check_token. For the accepted word, returns the flags
+    /// of its stem and the continuation flags of the removed affixes.
+    /// The word with FORBIDDENWORD flag, on the dictionary entry of the same form,
+    /// on its stem or on its affixes, is rejected. The entry of the same form is the final
+    /// authority, also when the word is accepted by other affixes or as a compound.
+    /// Of its homonyms differing in case, the word of the same case is used.
+    /// The word longer than slg_word_len_max isn't checked, it's rejected,
+    /// while the tokenizer skips it in the text.
+    pub fn check_token_flags(spell_lang: &SpellLang, word: &str) -> Option<Vec<String>> {
+        Spell::check_strip_flags(spell_lang, word, &mut AffixStrip::default())
+    }
+
+    /// Like check_token_flags, with the removed affixes of the accepted word (not a compound) in 'affix_strip'.
+    fn check_strip_flags(spell_lang: &SpellLang, word: &str, affix_strip: &mut AffixStrip) -> Option<Vec<String>> {
+        let found_flags = Spell::accepting_strip_flags(spell_lang, word, affix_strip)?;
+        if let Some(forbidden_flag) = spell_lang.special_flag(FlagType::FlagForbidden) {
+            if spell_lang.flags_contain(&found_flags, forbidden_flag) {
+                return None;
+            }
+            let (char_case, normalized_word) = Spell::decompose_token(spell_lang, word);
+            if let Some(dict_entry) = spell_lang.slg_dic_hash.get(normalized_word.as_str()) {
+                let same_form = dict_entry
+                    .flagged_words()
+                    .find(|flagged_word| flagged_word.flw_char_case == char_case)
+                    .unwrap_or(&dict_entry.den_words[0]);
+                if spell_lang.word_has_flag(same_form, forbidden_flag) {
+                    return None; // forbidden also when another form or a compound is accepted
+                }
+            }
+        }
+        Some(found_flags)
+    }
+
+    /// Returns the flags of the stem and the affixes for the word accepted by the dictionary,
+    /// without rejecting the forbidden words.
+    fn accepting_flags(spell_lang: &SpellLang, word: &str) -> Option<Vec<String>> {
+        Spell::accepting_strip_flags(spell_lang, word, &mut AffixStrip::default())
+    }
+
+    /// Like accepting_flags, with the removed affixes of the accepted word (not a compound) in 'affix_strip'.
+    fn accepting_strip_flags(spell_lang: &SpellLang, word: &str, affix_strip: &mut AffixStrip) -> Option<Vec<String>> {
+        let mut found_flags: Vec<String> = vec![];
+        if word.len() == 0 {
+            return Some(found_flags);
+        }
+        if !spell_lang.fits_word_len_max(word) {
+            return None; // too long to search, not a word in the tokenizer
+        }
+        /*
+        - Dictionary forms of the words can be uppercased in general text:
+        test, Test TEST
+        London london LONDON
+        HTML
+        - Letters within the word can be uppercased:
+        's-Gravenhage 'S-GRAVENHAGE
+        TikTok TIKTOK
+        - Dictionary forms of the words can be lowercased in internet addresses:
+        UNICEF (@unicef), unicef.org
+        - Full sentences with mixed character case
+        I visited the UNICEF web page unicef.org.
+        The official name of the Hague is 's-Gravenhage.
+        TikTok is well known.
+
+        */
+        let (char_case, normalized_word) = Spell::decompose_token(spell_lang, word);
+        let mut result = Spell::check_decased_word(
+            &spell_lang,
+            char_case,
+            &normalized_word,
+            None,
+            &mut found_flags,
+            affix_strip,
+            false,
+        );
+        if !result {
+            // let's trim the characters that are optionally in the word
+            let trimmed_word =
+                &normalized_word.trim_matches(|c| Tokenizer::from_lang(spell_lang).is_non_alphabetic_in_word(c));
+            result = Spell::check_decased_word(
+                &spell_lang,
+                char_case,
+                trimmed_word,
+                None,
+                &mut found_flags,
+                affix_strip,
+                false,
+            );
+        }
+        let compound_enabled = spell_lang.has_feature(CheckFeature::Compound);
+        if !result && compound_enabled {
+            let mut failed_states = HashSet::default();
+            result = Spell::check_compound(
+                spell_lang,
+                char_case,
+                &normalized_word,
+                CompoundPrev::default(),
+                &mut found_flags,
+                &mut failed_states,
+            );
+        }
+        if !result && compound_enabled && !spell_lang.slg_compoundrule_items.is_empty() {
+            let mut parts: Vec<&str> = vec![];
+            let rule_states: Vec<Vec<bool>> =
+                spell_lang.slg_compoundrule_items.iter().map(|rule| Spell::rule_start_states(rule)).collect();
+            let mut failed_states = HashSet::default();
+            result = Spell::check_compound_rules(
+                spell_lang,
+                char_case,
+                &normalized_word,
+                &mut parts,
+                &rule_states,
+                &mut failed_states,
+            );
+            for part in parts {
+                Spell::add_stem_flags(spell_lang, part, &mut found_flags);
+            }
+        }
+        //     fn is_non_alphabetic_in_word(&self, c:char) -> bool {
+
+        if result {Some(found_flags)} else {None}
+    }
+
+    /// Explains why the word is rejected, or that it's accepted. Unlike check_token,
+    /// it reports all the reasons found, e.g. for a forbidden word in the wrong case.
+    pub fn diagnose(spell_lang: &SpellLang, word: &str) -> RejectionInfo {
+        let mut reasons: Vec<RejectionReason> = vec![];
+        let found_flags = Spell::accepting_flags(spell_lang, word);
+        let (char_case, normalized_word) = Spell::decompose_token(spell_lang, word);
+        if let Some(dict_entry) = spell_lang.slg_dic_hash.get(normalized_word.as_str()) {
+            // of the homonyms differing in case, the word of the same case
+            let same_form = dict_entry
+                .flagged_words()
+                .find(|flagged_word| flagged_word.flw_char_case == char_case)
+                .unwrap_or(&dict_entry.den_words[0]);
+            if !Spell::word_present(spell_lang, char_case, &normalized_word, None, true) {
+                reasons.push(RejectionReason::CaseMismatch(same_form.flw_char_case));
+            }
+            let stem_reasons = [
+                (FlagType::FlagNeedAffix, RejectionReason::NeedsAffix),
+                (FlagType::FlagOnlyComp, RejectionReason::OnlyInCompound),
+            ];
+            for (flag_type, reason) in stem_reasons {
+                if let Some(flag) = spell_lang.special_flag(flag_type) {
+                    if spell_lang.word_has_flag(same_form, flag) {
+                        reasons.push(reason);
+                    }
+                }
+            }
+        }
+        if found_flags.is_some() && Spell::check_token_flags(spell_lang, word).is_none() {
+            // accepted by the affixes or compounding, but with FORBIDDENWORD
+            reasons.push(RejectionReason::Forbidden);
+        }
+        if found_flags.is_none() && reasons.is_empty() {
+            reasons.push(RejectionReason::NotInDictionary);
+        }
+        RejectionInfo { rji_reasons: reasons }
+    }
+
+    /// Checks one word and, only if it's rejected, finds the suggestions for it.
+    /// This is the single call for inline checking, e.g. in an editor.
+    pub fn check_word(spell_lang: &SpellLang, word: &str) -> WordResult {
+        if Spell::check_token(spell_lang, word) {
+            return WordResult { wrs_is_correct: true, wrs_suggestions: vec![] };
+        }
+        WordResult { wrs_is_correct: false, wrs_suggestions: Spell::suggest(spell_lang, word) }
+    }
+
+    /// Returns the keys left and right of the key 'c' in the rows of the layout,
+    /// e.g. "qwertyuiop|asdfghjkl|zxcvbnm" (the format of KEY).
+    /// See Parser::parse_key_layout for all the keys.
+    pub fn adjacent_keys(layout: &str, c: char) -> Vec<char> {
+        Parser::parse_key_layout(layout).remove(&c).unwrap_or_default()
+    }
+
+    /// The dictionary words differing from 'word' only in case, written in the case
+    /// of the dictionary, e.g. "London" for "london" or "iPod" for "ipod".
+    /// Then 'word' with the initial uppercase, e.g. "London's" for "london's".
+    fn case_variants(spell_lang: &SpellLang, word: &str) -> Vec<String> {
+        let lowercase = word.to_lowercase();
+        let mut variants: Vec<String> = vec![];
+        if let Some(dict_entry) = spell_lang.slg_dic_hash.get(lowercase.as_str()) {
+            variants.extend(
+                dict_entry
+                    .flagged_words()
+                    .map(|flagged_word| CharCase::restore_case(flagged_word.flw_char_case, &lowercase)),
+            );
+        }
+        // the keys of the mixed-case words, e.g. "iPod", are in the case of the dictionary
+        if let Some(mixed_keys) = spell_lang.slg_mixed_case_keys.get(&lowercase) {
+            variants.extend(mixed_keys.iter().map(|key| key.to_string()));
+        }
+        variants.push(CharCase::restore_case(CharCase::Initial, &lowercase));
+        variants
+    }
+
+    /// The variants of 'word' after the replacements from the REP table (a common misspelling
+    /// to its correction) or from the PHONE table, applied from the sound to the spelling,
+    /// e.g. "fone" to "phone" with "PHONE PH F". The PHONE rules with conditions are skipped.
+    /// Each occurrence is replaced alone, then all of them together, e.g. "fife" for "phiphe"
+    /// with "REP ph f". In REP, "_" is a space, e.g. "a lot" for "alot" with "REP alot a_lot",
+    /// and "^" and "$" anchor the replaced text at the start and at the end of the word.
+    fn table_variants(spell_lang: &SpellLang, word: &str) -> Vec<String> {
+        let phone_pairs = spell_lang
+            .slg_phone
+            .iter()
+            .filter(|(key, _)| key.chars().all(|c| c.is_alphabetic()))
+            .map(|(key, sound)| (sound.replace('_', "").to_lowercase(), key.to_lowercase()));
+        let rep_pairs = spell_lang.slg_rep.iter().map(|(from, to)| (from.replace('_', " "), to.replace('_', " ")));
+        let mut variants: Vec<String> = vec![];
+        for (from, to) in rep_pairs.chain(phone_pairs) {
+            let at_start = from.starts_with('^');
+            let at_end = from.ends_with('$') && from.len() > 1;
+            let from = from.trim_start_matches('^').trim_end_matches('$');
+            if from.is_empty() {
+                continue;
+            }
+            let positions: Vec<usize> = word
+                .match_indices(from)
+                .map(|(pos, _)| pos)
+                .filter(|pos| (!at_start || *pos == 0) && (!at_end || pos + from.len() == word.len()))
+                .collect();
+            for &pos in &positions {
+                variants.push(format!("{}{}{}", &word[..pos], to, &word[pos + from.len()..]));
+            }
+            if positions.len() > 1 {
+                variants.push(word.replace(from, &to));
+            }
+        }
+        variants
+    }
+
+    /// The variants of 'word' after one substitution by the MAP table, of a character
+    /// or a character sequence by another one from its group, e.g. "café" for "cafe" with "MAP eé".
+    fn map_variants(spell_lang: &SpellLang, word: &str) -> Vec<String> {
+        let mut variants: Vec<String> = vec![];
+        for (pos, _) in word.char_indices() {
+            let rest = &word[pos..];
+            for map_group in &spell_lang.slg_map_groups {
+                for member in map_group.iter().filter(|member| rest.starts_with(member.as_str())) {
+                    for other in map_group.iter().filter(|other| *other != member) {
+                        variants.push(format!("{}{}{}", &word[..pos], other, &rest[member.len()..]));
+                    }
+                }
+            }
+        }
+        variants
+    }
+
+    /// Returns the phonetic code of the word by the PHONE rules, e.g. "FN" for "phone"
+    /// with "PHONE 3", "PHONE PH F", "PHONE O _", "PHONE E _". See phonetic_code_by_rules.
+    pub fn phonetic_code(spell_lang: &SpellLang, word: &str) -> String {
+        Spell::phonetic_code_by_rules(&spell_lang.phone_rules(), word)
+    }
+
+    /// Returns the phonetic code of the uppercased word. At each position, the first rule
+    /// matching there replaces the matched characters, except the characters after
+    /// the rule's "-" marks that stay for the next rule. The characters not matched
+    /// by any rule are kept. As in aspell, a rule without "-" replacing several characters
+    /// is skipped when a longer rule of the same or a higher priority matches
+    /// from its last character, e.g. "AB" is skipped in "ABC" for "BC", but not for "BC1".
+    /// The replacement of a rule with "<" is put back into the word, and the rules
+    /// without "<" are applied to it, e.g. "PH<" to "F" and then "FF" in "PHF".
+    pub fn phonetic_code_by_rules(phone_rules: &[PhoneRule], word: &str) -> String {
+        let mut chars: Vec<char> = word.to_uppercase().chars().collect();
+        let mut phone_code = String::new();
+        let mut pos = 0;
+        let mut restarted = false;
+        while pos < chars.len() {
+            let matched_rule = phone_rules.iter().find_map(|phone_rule| {
+                if phone_rule.phr_restart && restarted {
+                    return None;
+                }
+                let matched_len = Spell::phone_rule_len(phone_rule, &chars, pos)?;
+                let replaced_len = matched_len.saturating_sub(phone_rule.phr_ahead).max(1);
+                if phone_rule.phr_ahead == 0 && replaced_len > 1 && pos + replaced_len < chars.len() {
+                    let followup_pos = pos + replaced_len - 1;
+                    let followed = phone_rules.iter().any(|followup_rule| {
+                        followup_rule.phr_priority >= phone_rule.phr_priority
+                            && Spell::phone_rule_len(followup_rule, &chars, followup_pos).is_some_and(|len| len > 1)
+                    });
+                    if followed {
+                        return None;
+                    }
+                }
+                Some((phone_rule, replaced_len))
+            });
+            match matched_rule {
+                Some((phone_rule, replaced_len)) if phone_rule.phr_restart => {
+                    chars.splice(pos..pos + replaced_len, phone_rule.phr_replacement.chars());
+                    restarted = true;
+                }
+                Some((phone_rule, replaced_len)) => {
+                    phone_code += &phone_rule.phr_replacement;
+                    pos += replaced_len;
+                    restarted = false;
+                }
+                None => {
+                    phone_code.push(chars[pos]);
+                    pos += 1;
+                    restarted = false;
+                }
+            }
+        }
+        phone_code
+    }
+
+    /// Returns the number of the characters matched by the PHONE rule at the position,
+    /// including the characters of its "-" marks.
+    fn phone_rule_len(phone_rule: &PhoneRule, chars: &[char], pos: usize) -> Option<usize> {
+        let rest = &chars[pos..];
+        if (phone_rule.phr_at_start && pos != 0) || !rest.starts_with(&phone_rule.phr_chars) {
+            return None;
+        }
+        let mut matched_len = phone_rule.phr_chars.len();
+        if !phone_rule.phr_group.is_empty() {
+            if !rest.get(matched_len).is_some_and(|c| phone_rule.phr_group.contains(c)) {
+                return None;
+            }
+            matched_len += 1;
+        }
+        if phone_rule.phr_at_end && pos + matched_len != chars.len() {
+            return None;
+        }
+        Some(matched_len)
+    }
+
+    /// Returns the dictionary words with the same phonetic code as the word, then the words
+    /// with the codes one edit away, at most MAXNGRAMSUGS, in their dictionary case
+    /// and with the case of an initial-uppercase or uppercase word.
+    fn phonetic_suggestions(spell_lang: &SpellLang, word: &str) -> Vec<String> {
+        let phone_code = Spell::phonetic_code(spell_lang, word);
+        let code_chars: Vec<char> = phone_code.chars().collect();
+        let mut near_codes: Vec<&String> = spell_lang
+            .slg_phone_codes
+            .keys()
+            .filter(|other_code| Spell::is_one_edit(&code_chars, &other_code.chars().collect::<Vec<char>>()))
+            .collect();
+        near_codes.sort_unstable();
+        let keys = spell_lang.slg_phone_codes.get(&phone_code).into_iter().chain(
+            near_codes.into_iter().filter_map(|near_code| spell_lang.slg_phone_codes.get(near_code)),
+        );
+        let (char_case, _) = CharCase::normalize_case(word);
+        keys.flatten()
+            .take(spell_lang.slg_max_ngram_sugs as usize)
+            .map(|key| Spell::suggestion_case(spell_lang, char_case, key))
+            .collect()
+    }
+
+    /// Returns the dictionary key in the case of the dictionary word, or of the misspelled word
+    /// when it's initial-uppercase or uppercase. Of the homonyms differing in case,
+    /// the word in the case of the misspelled word is preferred.
+    fn suggestion_case(spell_lang: &SpellLang, char_case: CharCase, key: &str) -> String {
+        match char_case {
+            CharCase::Initial | CharCase::Upper => CharCase::restore_case(char_case, key),
+            _ => {
+                let dict_entry = &spell_lang.slg_dic_hash[key];
+                let flagged_word = dict_entry
+                    .flagged_words()
+                    .find(|flagged_word| flagged_word.flw_char_case == char_case)
+                    .unwrap_or(&dict_entry.den_words[0]);
+                CharCase::restore_case(flagged_word.flw_char_case, key)
+            }
+        }
+    }
+
+    /// Returns the character n-grams (of one to three characters) of the word, with repeats,
+    /// each packed in a number by 21 bits per character.
+    fn word_ngrams(chars: &[char]) -> Vec<u64> {
+        let mut ngrams: Vec<u64> = vec![];
+        for n in 1..=3 {
+            ngrams.extend(chars.windows(n).map(|ngram| ngram.iter().fold(0, |packed, c| packed << 21 | (*c as u64 + 1))));
+        }
+        ngrams
+    }
+
+    /// Returns the dictionary words most similar to the word, at most MAXNGRAMSUGS, the most similar first.
+    /// The score of the similarity is the number of the n-grams of the word present in the dictionary word
+    /// (see word_ngrams), less the difference of the lengths. The words below the similarity threshold
+    /// of MAXDIFF (0 to 10, by default 5, the higher the more different words) are left out,
+    /// except the most similar one without ONLYMAXDIFF. The words differing in length by more than
+    /// the word itself, and too much to reach the threshold, are left out too.
+    /// The n-grams are counted in slg_ngram_index, still each dictionary word is scored, so it's used
+    /// only when no closer suggestion was found. With 150 000 words of 5 to 12 characters, it takes
+    /// about 4 ms for a word of 10 characters, and indexing the words about 0.4 s on loading
+    /// (without the index, comparing the n-grams of each word took 190 ms for each misspelling).
+    fn ngram_suggestions(spell_lang: &SpellLang, word: &str) -> Vec<String> {
+        if spell_lang.slg_max_ngram_sugs == 0 {
+            return vec![];
+        }
+        let (char_case, decased_word) = CharCase::normalize_case(word);
+        let chars: Vec<char> = decased_word.chars().collect();
+        let ngrams = Spell::word_ngrams(&chars);
+        let self_score = ngrams.len() as i32;
+        let threshold = self_score * (10 - spell_lang.slg_max_diff.min(10) as i32) / 10;
+        let len_diff_max = chars.len().max((self_score - threshold) as usize);
+        let ngram_index = &spell_lang.slg_ngram_index;
+        let mut shared_counts: Vec<u32> = vec![0; ngram_index.ngx_keys.len()];
+        for ngram in &ngrams {
+            for key_ix in ngram_index.ngx_key_ixs.get(ngram).into_iter().flatten() {
+                shared_counts[*key_ix as usize] += 1;
+            }
+        }
+        let mut scored: Vec<(i32, &Arc<str>)> = shared_counts
+            .iter()
+            .zip(&ngram_index.ngx_char_counts)
+            .zip(&ngram_index.ngx_keys)
+            .filter_map(|((shared_count, char_count), key)| {
+                let len_diff = (*char_count as usize).abs_diff(chars.len());
+                let score = *shared_count as i32 - len_diff as i32;
+                (len_diff <= len_diff_max && score > 0).then_some((score, key))
+            })
+            .collect();
+        // the words of the same score are sorted only when they're reached
+        scored.sort_unstable_by_key(|(score, _)| Reverse(*score));
+        let mut suggestions: Vec<String> = vec![];
+        'scores: for same_scored in scored.chunk_by_mut(|a, b| a.0 == b.0) {
+            same_scored.sort_unstable_by(|a, b| a.1.cmp(b.1));
+            for (score, key) in same_scored.iter() {
+                if suggestions.len() >= spell_lang.slg_max_ngram_sugs as usize {
+                    break 'scores;
+                }
+                if *score < threshold && (spell_lang.slg_only_max_diff || !suggestions.is_empty()) {
+                    break 'scores;
+                }
+                let suggestion = Spell::suggestion_case(spell_lang, char_case, key);
+                if Spell::check_token(spell_lang, &suggestion)
+                    && !Spell::token_has_special_flag(spell_lang, &suggestion, FlagType::FlagNoSuggest)
+                {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+        suggestions
+    }
+
+    /// Returns true if the strings differ by one substitution, insertion, deletion
+    /// or transposition of neighbouring characters.
+    fn is_one_edit(a: &[char], b: &[char]) -> bool {
+        let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+        if longer.len() - shorter.len() > 1 {
+            return false;
+        }
+        let prefix_len = shorter.iter().zip(longer).take_while(|(x, y)| x == y).count();
+        if prefix_len == longer.len() {
+            return false;
+        }
+        if shorter.len() < longer.len() {
+            return shorter[prefix_len..] == longer[prefix_len + 1..];
+        }
+        shorter[prefix_len + 1..] == longer[prefix_len + 1..]
+            || prefix_len + 1 < shorter.len()
+                && shorter[prefix_len] == longer[prefix_len + 1]
+                && shorter[prefix_len + 1] == longer[prefix_len]
+                && shorter[prefix_len + 2..] == longer[prefix_len + 2..]
+    }
+
+    /// Returns the word in the form looked up in the dictionary, but in its own case:
+    /// after ICONV and without the IGNORE characters, e.g. "word" for "wo\u{ad}rd" with "IGNORE \u{ad}".
+    fn checked_form(spell_lang: &SpellLang, word: &str) -> String {
+        let converted_word = if spell_lang.slg_iconv.is_empty() {
+            word.to_string()
+        } else {
+            Spell::convert_word(&spell_lang.slg_iconv, word)
+        };
+        converted_word.chars().filter(|c| !spell_lang.slg_ignore.contains(*c)).collect()
+    }
+
+    /// Returns the transpositions of the neighbouring characters and the deletions of the characters.
+    fn swaps_and_deletions(chars: &[char]) -> Vec<String> {
+        let mut edited: Vec<String> = vec![];
+        for pos in 1..chars.len() {
+            let mut swapped = chars.to_vec();
+            swapped.swap(pos - 1, pos);
+            edited.push(swapped.iter().collect());
+        }
+        for pos in 0..chars.len() {
+            let mut deleted = chars.to_vec();
+            deleted.remove(pos);
+            edited.push(deleted.iter().collect());
+        }
+        edited
+    }
+
+    /// Adds the accepted candidates to the suggestions, without the duplicates, the word itself
+    /// and the words with NOSUGGEST flag, until the maximum count.
+    fn add_suggestions(spell_lang: &SpellLang, word: &str, candidates: Vec<String>, suggestions: &mut Vec<String>) {
+        for candidate in candidates {
+            if suggestions.len() >= spell_lang.slg_suggest_max as usize {
+                break;
+            }
+            if candidate.is_empty() || candidate == word || suggestions.contains(&candidate) {
+                continue;
+            }
+            // the words separated by a space, e.g. from REP
+            if candidate.split(' ').all(|part| {
+                Spell::check_token(spell_lang, part)
+                    && !Spell::token_has_special_flag(spell_lang, part, FlagType::FlagNoSuggest)
+            }) {
+                suggestions.push(candidate);
+            }
+        }
+    }
+
+    /// Returns the ranked suggestions for the misspelled word, at most slg_suggest_max.
+    /// First, the word in the case of the dictionary (see case_variants),
+    /// then the word with the misspelling in its ph: field and a replacement
+    /// from the REP or PHONE table (see table_variants) or from the MAP table (see map_variants).
+    /// Then the words one edit away: a substitution of a neighbouring key (see keyboard_layout),
+    /// a transposition of neighbouring characters, a deletion, or a substitution or insertion
+    /// of a character from the TRY string or the dictionary (see suggest_alphabet),
+    /// or a space between two words unless NOSPLITSUGS, in that order.
+    /// Only when there are fewer suggestions, the words sounding similar by the PHONE table
+    /// (see phonetic_suggestions) and the words two transpositions or deletions away.
+    /// Only without any suggestion, the dictionary words sharing most n-grams (see ngram_suggestions).
+    /// The edits are made on the checked form of the word (see checked_form),
+    /// the suggestions are written with OCONV. The features of slg_features can disable
+    /// all the suggestions, or the phonetic or n-gram ones. The word longer than slg_word_len_max
+    /// isn't corrected.
+    pub fn suggest(spell_lang: &SpellLang, word: &str) -> Vec<String> {
+        if !spell_lang.has_feature(CheckFeature::Suggest) || !spell_lang.fits_word_len_max(word) {
+            return vec![];
+        }
+        let checked_word = Spell::checked_form(spell_lang, word);
+        let word = checked_word.as_str();
+        let chars: Vec<char> = word.chars().collect();
+        let alphabet = spell_lang.suggest_alphabet();
+        // first, the same word in another case, e.g. "London" for "london"
+        let mut candidates: Vec<String> = Spell::case_variants(spell_lang, word);
+        // the words having the misspelling in their ph: field
+        let (char_case, decased_word) = CharCase::normalize_case(word);
+        if let Some(hinted) = spell_lang.slg_ph_hints.get(&decased_word) {
+            candidates.push(match char_case {
+                CharCase::Initial | CharCase::Upper => CharCase::restore_case(char_case, hinted),
+                _ => hinted.clone(),
+            });
+        }
+        // the language specific confusions
+        candidates.extend(Spell::table_variants(spell_lang, word));
+        candidates.extend(Spell::map_variants(spell_lang, word));
+        // the substitutions of the neighbouring keys of the keyboard
+        let adjacency = Parser::parse_key_layout(spell_lang.keyboard_layout());
+        for pos in 0..chars.len() {
+            for &c in adjacency.get(&chars[pos]).into_iter().flatten() {
+                let mut substituted = chars.clone();
+                substituted[pos] = c;
+                candidates.push(substituted.iter().collect());
+            }
+        }
+        let swapped_or_deleted = Spell::swaps_and_deletions(&chars);
+        candidates.extend(swapped_or_deleted.iter().cloned());
+        for &c in &alphabet {
+            for pos in 0..chars.len() {
+                if chars[pos] != c {
+                    let mut substituted = chars.clone();
+                    substituted[pos] = c;
+                    candidates.push(substituted.iter().collect());
+                }
+            }
+        }
+        for &c in &alphabet {
+            for pos in 0..=chars.len() {
+                let mut inserted = chars.clone();
+                inserted.insert(pos, c);
+                candidates.push(inserted.iter().collect());
+            }
+        }
+        if spell_lang.slg_sug_split {
+            // the missing space, e.g. "the cat" for "thecat"
+            for (pos, _) in word.char_indices().skip(1) {
+                candidates.push(format!("{} {}", &word[..pos], &word[pos..]));
+            }
+        }
+        let mut suggestions: Vec<String> = vec![];
+        Spell::add_suggestions(spell_lang, word, candidates, &mut suggestions);
+        if suggestions.len() < spell_lang.slg_suggest_max as usize
+            && !spell_lang.slg_phone_codes.is_empty()
+            && spell_lang.has_feature(CheckFeature::Phonetic)
+        {
+            let candidates = Spell::phonetic_suggestions(spell_lang, word);
+            Spell::add_suggestions(spell_lang, word, candidates, &mut suggestions);
+        }
+        if suggestions.len() < spell_lang.slg_suggest_max as usize {
+            // the longer edit distance
+            let candidates: Vec<String> = swapped_or_deleted
+                .iter()
+                .flat_map(|edited| Spell::swaps_and_deletions(&edited.chars().collect::<Vec<char>>()))
+                .collect();
+            Spell::add_suggestions(spell_lang, word, candidates, &mut suggestions);
+        }
+        if suggestions.is_empty() && spell_lang.has_feature(CheckFeature::Ngram) {
+            let candidates = Spell::ngram_suggestions(spell_lang, word);
+            Spell::add_suggestions(spell_lang, word, candidates, &mut suggestions);
+        }
+        if !spell_lang.slg_oconv.is_empty() {
+            for suggestion in &mut suggestions {
+                *suggestion = Spell::convert_word(&spell_lang.slg_oconv, suggestion);
+            }
+        }
+        suggestions
+    }
+
+    /// Returns the byte ranges of the words repeating the previous word, separated from it
+    /// only by whitespace, e.g. the second "the" in "the the end". The case is not compared.
+    pub fn repeated_word_spans(spell_lang: &SpellLang, untokenized_text: &str) -> Vec<Range<usize>> {
+        let mut repeated: Vec<Range<usize>> = vec![];
+        let mut prev_word: Option<String> = None;
+        for (span, token_type) in Tokenizer::from_lang(spell_lang).tokenize_spans(untokenized_text) {
+            let token = &untokenized_text[span.clone()];
+            if token_type == TokenType::IsWord {
+                let word = token.to_lowercase();
+                if prev_word.as_ref() == Some(&word) {
+                    repeated.push(span);
+                }
+                prev_word = Some(word);
+            } else if !token.chars().all(char::is_whitespace) {
+                prev_word = None;
+            }
+        }
+        repeated
+    }
+
+    /// Checks the text and returns the misspelled words, with their suggestions,
+    /// and the repeated words (see repeated_word_spans), in the order of the text.
+    pub fn check_text_issues(spell_lang: &SpellLang, untokenized_text: &str) -> Vec<Issue> {
+        let mut issues: Vec<Issue> = Spell::check_text_spans(spell_lang, untokenized_text)
+            .into_iter()
+            .filter(|(_, token_type)| *token_type == TokenType::IsBadWord)
+            .map(|(span, _)| Issue {
+                isu_kind: IssueKind::Misspelling,
+                isu_suggestions: Spell::suggest(spell_lang, &untokenized_text[span.clone()]),
+                isu_span: span,
+            })
+            .collect();
+        for span in Spell::repeated_word_spans(spell_lang, untokenized_text) {
+            issues.push(Issue { isu_kind: IssueKind::RepeatedWord, isu_span: span, isu_suggestions: vec![] });
+        }
+        issues.sort_by_key(|issue| issue.isu_span.start);
+        issues
+    }
+
+    /// Check several words or paragraph, not yet tokenized.
+    /// Returns the byte ranges of the tokens in `untokenized_text` with their types.
+    /// The ranges are in the original text, also when the checked word differs
+    /// from it, after ICONV conversion or after removing IGNORE characters.
+    pub fn check_text_spans(
+        spell_lang: &SpellLang,
+        untokenized_text: &str,
+    ) -> Vec<(Range<usize>, TokenType)> {
+        let mut spans = Tokenizer::from_lang(spell_lang).tokenize_spans(untokenized_text);
+        for (span, token_type) in &mut spans {
+            if *token_type != TokenType::IsWord {
                 continue;
             }
-            let check_result = Spell::check_token(&spell_lang, &word);
+            let check_result = Spell::check_token(spell_lang, &untokenized_text[span.clone()]);
             // todo depending on spl_check_level, let the function return more info
             *token_type = if check_result {TokenType::IsGoodWord} else {TokenType::IsBadWord};
         }
-        tokens
+        spans
+    }
+
+    /// Check several words or paragraph, not yet tokenized.
+    pub fn check_text(
+        spell_lang: &SpellLang,
+        untokenized_text: &str,
+    ) -> Vec<(String, TokenType)> {
+        Spell::check_text_spans(spell_lang, untokenized_text)
+            .into_iter()
+            .map(|(span, token_type)| (untokenized_text[span].to_string(), token_type))
+            .collect()
+    }
+
+    /// Returns the indexes of the word tokens starting a sentence:
+    /// the first word and any word with initial uppercase after the sentence-ending
+    /// punctuation and a whitespace. The punctuation is given in 'sentence_ends',
+    /// typically Spell::SENTENCE_ENDS. It can be a separate token or the end of word,
+    /// when it's among the WORDCHARS (e.g. "etc.").
+    pub fn sentence_starts(tokens: &[(String, TokenType)], sentence_ends: &str) -> Vec<usize> {
+        let mut starts: Vec<usize> = vec![];
+        let mut is_first_word = true;
+        let mut after_end = false;
+        let mut after_space = false;
+        for (token_ix, (token, token_type)) in tokens.iter().enumerate() {
+            if *token_type == TokenType::NotWord {
+                // other punctuation, e.g. quotes in 'end." "Next', doesn't change anything
+                if token.chars().all(|c| c.is_whitespace()) {
+                    after_space = true;
+                } else if token.chars().all(|c| sentence_ends.contains(c)) {
+                    after_end = true;
+                    after_space = false;
+                }
+                continue;
+            }
+            let is_upper = token.chars().next().is_some_and(|c| c.is_uppercase());
+            if is_first_word || after_end && after_space && is_upper {
+                starts.push(token_ix);
+            }
+            is_first_word = false;
+            after_end = token.chars().last().is_some_and(|c| sentence_ends.contains(c));
+            after_space = false;
+        }
+        starts
+    }
+
+    /// Returns the code of the first dictionary in 'spell_langs' accepting the word.
+    /// The dictionaries are tried in order, so the earlier ones have priority.
+    pub fn check_token_langs<'l>(spell_langs: &'l [SpellLang], word: &str) -> Option<&'l str> {
+        spell_langs
+            .iter()
+            .find(|spell_lang| Spell::check_token(spell_lang, word))
+            .map(|spell_lang| spell_lang.slg_code.as_str())
+    }
+
+    /// Check several words or paragraph with several dictionaries, e.g. a base
+    /// dictionary and its supplement. A word is good if any dictionary accepts it.
+    /// The text is tokenized as defined by the first dictionary.
+    /// For good words, the code of the accepting dictionary is returned, too.
+    pub fn check_text_langs<'l>(
+        spell_langs: &'l [SpellLang],
+        untokenized_text: &str,
+    ) -> Vec<(String, TokenType, Option<&'l str>)> {
+        if spell_langs.is_empty() {
+            return vec![];
+        }
+        let tokens = Tokenizer::from_lang(&spell_langs[0]).tokenize(untokenized_text);
+        let mut checked_tokens = Vec::with_capacity(tokens.len());
+        for (word, token_type) in tokens {
+            if word.is_empty() || token_type != TokenType::IsWord {
+                checked_tokens.push((word, token_type, None));
+                continue;
+            }
+            let lang_code = Spell::check_token_langs(spell_langs, &word);
+            let token_type = if lang_code.is_some() {TokenType::IsGoodWord} else {TokenType::IsBadWord};
+            checked_tokens.push((word, token_type, lang_code));
+        }
+        checked_tokens
+    }
+
+    /// Compares which of the words are accepted by 'base_lang' and by 'new_lang',
+    /// e.g. by two versions of a dictionary. Returns the words newly accepted
+    /// and the words newly rejected by 'new_lang', in the order of 'words'.
+    pub fn diff_accepted<'w>(
+        base_lang: &SpellLang,
+        new_lang: &SpellLang,
+        words: &'w [String],
+    ) -> (Vec<&'w str>, Vec<&'w str>) {
+        let mut accepted: Vec<&str> = vec![];
+        let mut rejected: Vec<&str> = vec![];
+        for word in words {
+            let base_good = Spell::check_token(base_lang, word);
+            let new_good = Spell::check_token(new_lang, word);
+            if new_good && !base_good {
+                accepted.push(word);
+            } else if base_good && !new_good {
+                rejected.push(word);
+            }
+        }
+        (accepted, rejected)
+    }
+
+    /// Check the text mixing several languages without markup. For each sentence,
+    /// the language accepting most of its words is selected, the earlier dictionaries
+    /// win on equal counts. The words of the sentence are then checked only with that
+    /// language, so a word accepted by another language is reported as wrong.
+    /// The code of the selected language is returned for all the words of the sentence.
+    pub fn check_text_guess_langs<'l>(
+        spell_langs: &'l [SpellLang],
+        untokenized_text: &str,
+    ) -> Vec<(String, TokenType, Option<&'l str>)> {
+        if spell_langs.is_empty() {
+            return vec![];
+        }
+        let tokens = Tokenizer::from_lang(&spell_langs[0]).tokenize(untokenized_text);
+        let mut run_starts = Spell::sentence_starts(&tokens, Spell::SENTENCE_ENDS);
+        if run_starts.first() != Some(&0) {
+            run_starts.insert(0, 0); // the leading non-words
+        }
+        run_starts.push(tokens.len());
+        let mut checked_tokens = Vec::with_capacity(tokens.len());
+        for run in run_starts.windows(2) {
+            let run_tokens = &tokens[run[0]..run[1]];
+            // accepted[lang_ix][word_ix] for the word tokens of the run
+            let accepted: Vec<Vec<bool>> = spell_langs
+                .iter()
+                .map(|spell_lang| {
+                    run_tokens
+                        .iter()
+                        .filter(|(word, token_type)| !word.is_empty() && *token_type == TokenType::IsWord)
+                        .map(|(word, _)| Spell::check_token(spell_lang, word))
+                        .collect()
+                })
+                .collect();
+            let mut best_ix = 0;
+            let mut best_count = 0;
+            for (lang_ix, lang_accepted) in accepted.iter().enumerate() {
+                let count = lang_accepted.iter().filter(|good| **good).count();
+                if count > best_count {
+                    best_ix = lang_ix;
+                    best_count = count;
+                }
+            }
+            let lang_code = Some(spell_langs[best_ix].slg_code.as_str());
+            let mut word_ix = 0;
+            for (word, token_type) in run_tokens {
+                if word.is_empty() || *token_type != TokenType::IsWord {
+                    checked_tokens.push((word.clone(), *token_type, None));
+                    continue;
+                }
+                let good = accepted[best_ix][word_ix];
+                word_ix += 1;
+                let token_type = if good {TokenType::IsGoodWord} else {TokenType::IsBadWord};
+                checked_tokens.push((word.clone(), token_type, lang_code));
+            }
+        }
+        checked_tokens
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::core_speller::Regex;
+    use crate::core_speller::{CharCase, ModeFlag, Regex, Spell, SpellLang, TokenType, Tokenizer};
 
     #[test]
     fn regex_test() {
@@ -772,4 +3541,107 @@ mod tests {
         assert_eq!(regex2.match_edge("regat", false), false);
         assert_eq!(regex2.match_edge("regito", false), false);
     }
+
+    #[test]
+    fn convert_word_test() {
+        let conv_table = vec![
+            (String::from("oe"), String::from("œ")),
+            (String::from("o"), String::from("ó")),
+            (String::from("_ʼ"), String::from("'")),
+        ];
+        assert_eq!(Spell::convert_word(&conv_table, "coeur"), "cœur");
+        assert_eq!(Spell::convert_word(&conv_table, "cor"), "cór");
+        assert_eq!(Spell::convert_word(&conv_table, "ʼtisʼ"), "'tisʼ");
+    }
+
+    #[cfg(feature = "fxhash")]
+    #[test]
+    fn stable_order_test() {
+        use crate::core_speller::HashMap;
+        let words = ["spell", "game", "word", "respell", "gaming", "world"];
+        let build = || -> HashMap<String, u32> {
+            let mut map: HashMap<String, u32> = HashMap::default();
+            for (ix, word) in words.iter().enumerate() {
+                map.insert(word.to_string(), ix as u32);
+            }
+            map
+        };
+        // the fixed hasher gives the same order to the maps built the same way,
+        // unlike a randomly seeded one, which is seeded anew for each map
+        let (map1, map2) = (build(), build());
+        let keys1: Vec<&String> = map1.keys().collect();
+        let keys2: Vec<&String> = map2.keys().collect();
+        assert_eq!(keys1, keys2);
+    }
+
+    #[test]
+    fn tokenize_no_letters_test() {
+        let mut spell_lang = SpellLang::new("test");
+        spell_lang.slg_wordchars = vec!['-', '.'];
+        let checked = Spell::check_text(&spell_lang, "--- ... x-ray");
+        let bad_words: Vec<&str> = checked
+            .iter()
+            .filter(|token| token.1 == TokenType::IsBadWord)
+            .map(|token| token.0.as_str())
+            .collect();
+        assert_eq!(bad_words, vec!["x-ray"]);
+    }
+
+    #[test]
+    fn tokenize_underscore_test() {
+        let mut spell_lang = SpellLang::new("test");
+        let words = |spell_lang: &SpellLang| -> Vec<String> {
+            Tokenizer::from_lang(spell_lang).tokenize("a foo_bar")
+                .into_iter()
+                .filter(|token| token.1 == TokenType::IsWord)
+                .map(|token| token.0)
+                .collect()
+        };
+        assert_eq!(words(&spell_lang), vec!["a", "foo", "bar"]);
+        spell_lang.slg_mode_flags |= ModeFlag::Identifiers as u32;
+        assert_eq!(words(&spell_lang), vec!["a", "foo_bar"]);
+    }
+
+    #[test]
+    fn tokenizer_test() {
+        let tokenizer = Tokenizer { tkn_wordchar_digits: true, ..Tokenizer::new(&['-', '\'']) };
+        let tokens = tokenizer.tokenize("It's a 2-way e-mail, 'quoted' -- 42.");
+        let words: Vec<&str> = tokens
+            .iter()
+            .filter(|token| token.1 == TokenType::IsWord)
+            .map(|token| token.0.as_str())
+            .collect();
+        assert_eq!(words, vec!["It's", "a", "2-way", "e-mail", "'quoted'"]);
+        let not_words: Vec<&str> = tokens
+            .iter()
+            .filter(|token| token.1 == TokenType::NotWord && token.0 != " ")
+            .map(|token| token.0.as_str())
+            .collect();
+        assert_eq!(not_words, vec![",", "--", "42", "."]);
+    }
+
+    #[test]
+    fn sentence_starts_test() {
+        let spell_lang = SpellLang::new("test");
+        let tokens = Spell::check_text(&spell_lang, "One sentence. Two here! three (no)? Four");
+        let starts: Vec<&str> = Spell::sentence_starts(&tokens, Spell::SENTENCE_ENDS)
+            .into_iter()
+            .map(|token_ix| tokens[token_ix].0.as_str())
+            .collect();
+        assert_eq!(starts, vec!["One", "Two", "Four"]);
+    }
+
+    #[test]
+    fn case_round_trip_test() {
+        let cases = [
+            ("web", CharCase::Lower, "web"),
+            ("Hague", CharCase::Initial, "hague"),
+            ("UNICEF", CharCase::Upper, "unicef"),
+            ("TikTok", CharCase::Other, "TikTok"),
+        ];
+        for (word, char_case, key) in cases {
+            assert_eq!(CharCase::decompose(word), (char_case, key.to_string()));
+            assert_eq!(CharCase::restore_case(char_case, key), word);
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use crate::core_speller::{
-    HashMap, HashSet,AffixEntry, AffixClass, DicEntry, FlagFormat, FlagNameAndType, FlagType, FlaggedWord, SpellLang,
+    HashMap, HashSet,AffixEntry, AffixClass, CharCase, CheckFeature, CompoundRuleItem, DicEntry, FlagFormat, FlagNameAndType, FlagType, FlaggedWord, PhoneRule, SkipPattern, Spell, SpellLang,
 };
 use std::str::SplitWhitespace;
 
@@ -11,6 +11,9 @@ pub trait LineReader {
     fn read_line(&mut self, ) -> Option<Vec::<u8>>;
 }
 
+/// Occurrences of the notes in a file: (description, details) -> (order of the first one, count).
+type NoteRepeats = HashMap<(&'static str, Option<String>), (usize, u32)>;
+
 /// Comment on a single line or a problem.
 pub struct ParseNote {
     pub psn_line_no: u32, // 0 no data; when given > 0
@@ -127,54 +130,103 @@ impl<'a> LineParseState<'a> {
 
 pub struct Parser {}
 impl Parser {
-    /// Parses string with multiple flags.
-    /// With FLAG UTF-8, each flag is one character, multiple flags are not separated.
-    /// With FLAG long, each flag is two characters, multiple flags are not separated
-    /// With FLAG num, each flag is an unsigned number, multiple flags are separated by commas
-    fn parse_flags(spell_lang: &SpellLang, flags: &str) -> Vec<String> {
-        if flags.len() == 0 {
-            return vec![];
-        }
-        if spell_lang.slg_flag == FlagFormat::SingleUni {
-            // one-character flags
-            return flags.chars().map(|c| c.to_string()).collect();
-        }
-        if spell_lang.slg_flag == FlagFormat::DoubleChar {
-            // two-character flags
-            let mut flag_vec: Vec<String> = vec![];
-            let mut flag_chars = "".to_string();
-            for c in flags.chars() {
-                if flag_chars.is_empty() {
-                    flag_chars = c.to_string();
-                } else {
-                    flag_chars.push(c);
-                    flag_vec.push(flag_chars.clone());
-                    flag_chars = "".to_string();
-                }
-            }
-            return flag_vec;
+    /// The tags of the aff files known from Hunspell documentation,
+    /// including the ones that are not (yet) supported.
+    pub const KNOWN_TAGS: [&'static str; 64] = [
+        "AF", "AM", "BREAK", "CHECKCOMPOUNDCASE", "CHECKCOMPOUNDDUP", "CHECKCOMPOUNDPATTERN",
+        "CHECKCOMPOUNDREP", "CHECKCOMPOUNDTRIPLE", "CHECKSHARPS", "CIRCUMFIX", "COMPLEXPREFIXES",
+        "COMPOUNDBEGIN", "COMPOUNDEND", "COMPOUNDFIRST", "COMPOUNDFLAG", "COMPOUNDFORBIDFLAG",
+        "COMPOUNDLAST", "COMPOUNDMIDDLE", "COMPOUNDMIN", "COMPOUNDMORESUFFIXES",
+        "COMPOUNDPERMITFLAG", "COMPOUNDROOT", "COMPOUNDRULE", "COMPOUNDSYLLABLE",
+        "COMPOUNDWORDMAX", "FLAG", "FORBIDDENWORD", "FORBIDWARN", "FORCEUCASE", "FULLSTRIP",
+        "HOME", "ICONV", "IGNORE", "KEEPCASE", "KEY", "LANG", "LEMMA_PRESENT", "MAP", "MAXCPDSUGS",
+        "MAXDIFF", "MAXNGRAMSUGS", "NAME", "NEEDAFFIX", "NOSPLITSUGS", "NOSUGGEST", "OCONV",
+        "ONLYINCOMPOUND", "ONLYMAXDIFF", "PFX", "PHONE", "PSEUDOROOT", "REP", "SET", "SFX",
+        "SIMPLIFIEDTRIPLE", "SUBSTANDARD", "SUGSWITHDOTS", "SYLLABLENUM", "TRY", "VERSION", "WARN",
+        "WORDCHARS", "PREFIXMAX", "SUFFIXMAX",
+    ];
+
+    /// The tags defining a flag with a special meaning, e.g. "NOSUGGEST !".
+    pub const SIMPLE_FLAG_TAGS: [FlagNameAndType; 18] = [
+        ("COMPOUNDFLAG", FlagType::FlagCompound),
+        ("COMPOUNDBEGIN", FlagType::FlagCompBegin),
+        ("COMPOUNDLAST", FlagType::FlagCompLast),
+        ("COMPOUNDMIDDLE", FlagType::FlagCompMid),
+        ("COMPOUNDEND", FlagType::FlagCompEnd),
+        ("ONLYINCOMPOUND", FlagType::FlagOnlyComp),
+        ("COMPOUNDPERMITFLAG", FlagType::FlagCompPermit),
+        ("COMPOUNDFORBIDFLAG", FlagType::FlagCompForbid),
+        ("COMPOUNDROOT", FlagType::FlagCompRoot),
+        ("NEEDAFFIX", FlagType::FlagNeedAffix),
+        ("CIRCUMFIX", FlagType::FlagCircumfix),
+        ("FORBIDDENWORD", FlagType::FlagForbidden),
+        ("SUBSTANDARD", FlagType::FlagSubstandard),
+        ("NOSUGGEST", FlagType::FlagNoSuggest),
+        ("KEEPCASE", FlagType::FlagKeepCase),
+        ("FORCEUCASE", FlagType::FlagForceUcase),
+        ("WARN", FlagType::FlagWarn),
+        ("LEMMA_PRESENT", FlagType::FlagLemma),
+    ];
+
+    /// Returns true if the tag was not parsed and it's not among Parser::KNOWN_TAGS,
+    /// e.g. the misspelled "COMPUNDFLAG". Comments are not tags.
+    pub fn is_unknown_tag(spell_lang: &SpellLang, tag: &str) -> bool {
+        !tag.starts_with('#')
+            && spell_lang.slg_noparse_tags.contains_key(tag)
+            && !Parser::KNOWN_TAGS.contains(&tag)
+    }
+
+    /// Parses string with multiple flags, see SpellLang::split_flags.
+    /// With FLAG long, an unpaired last character is reported and dropped.
+    /// After AF tag, the flags can be given by the number of AF entry.
+    fn parse_flags(
+        spell_lang: &SpellLang,
+        flags: &str,
+        parse_state: &mut LineParseState,
+    ) -> Vec<String> {
+        if spell_lang.slg_af_parsed && !flags.is_empty() && flags.chars().all(|c| c.is_ascii_digit()) {
+            // "AF 2", "AF Ab", "AF AbCd", "word/2"
+            return vec![flags.to_string()];
         }
-        if spell_lang.slg_flag == FlagFormat::Numeric {
-            return flags.split(",").map(|s| s.to_string()).collect();
+        if spell_lang.slg_flag == FlagFormat::DoubleChar && flags.chars().count() % 2 != 0 {
+            parse_state.add_note2("Odd number of characters in long flags", &flags.to_string());
         }
-        vec![]
+        spell_lang.split_flags(flags)
     }
 
     /// Parses COMPOUNDRULE string with multiple flags.
     /// Asterisk, question mark and parenthesis are regex characters.
     /// SingleChar and SingleUni flags are all the remaining characters: mn*t,
-    /// DoubleChar and Numeric flags are enclosed in parentheses.
-    /// Returns the vector of flags.
-    fn parse_compoundrule_flags(spell_lang: &SpellLang, flags: &str) -> Vec<String> {
-        if spell_lang.slg_flag == FlagFormat::SingleUni {
-            // one-character flags
-            return flags
-                .chars()
-                .map(|fl| fl.to_string())
-                .filter(|fl| fl != "*" && fl != "?")
-                .collect();
+    /// DoubleChar and Numeric flags are enclosed in parentheses: (aa)(bb)*(cc), (1)(2)?(3).
+    /// Returns the flags, each with the following quantifier or space.
+    pub fn parse_compoundrule(spell_lang: &SpellLang, rule: &str) -> Vec<CompoundRuleItem> {
+        let mut items: Vec<CompoundRuleItem> = vec![];
+        let mut chars = rule.chars();
+        while let Some(c) = chars.next() {
+            if c == '*' || c == '?' {
+                if let Some(item) = items.last_mut() {
+                    item.1 = c;
+                }
+                continue;
+            }
+            let flag = if c == '(' {
+                let mut flag = String::new();
+                for c in chars.by_ref() {
+                    if c == ')' {
+                        break;
+                    }
+                    flag.push(c);
+                }
+                flag
+            } else if spell_lang.slg_flag == FlagFormat::SingleChar || spell_lang.slg_flag == FlagFormat::SingleUni {
+                c.to_string()
+            } else {
+                // only the parenthesized flags are expected
+                continue;
+            };
+            items.push((flag, ' '));
         }
-        vec![]
+        items
     }
 
     /// Parses the tag without value, acting as bool.
@@ -263,6 +315,9 @@ impl Parser {
             ("MAXCPDSUGS", &mut spell_lang.slg_max_cpd_sugs),
             ("MAXNGRAMSUGS", &mut spell_lang.slg_max_ngram_sugs),
             ("MAXDIFF", &mut spell_lang.slg_max_diff),
+            // neaspell extensions, overriding the limits set by COMPLEXPREFIXES
+            ("PREFIXMAX", &mut spell_lang.slg_prefix_max),
+            ("SUFFIXMAX", &mut spell_lang.slg_suffix_max),
         ];
         let mut result = false;
         for (tag, variab) in parse_table {
@@ -271,6 +326,10 @@ impl Parser {
                     let number_value = number_value.parse::<u32>();
                     if let Ok(number_value) = number_value {
                         *variab = number_value;
+                        if number_value == 0 && tag == "COMPOUNDWORDMAX" {
+                            // the same as without the tag, not a limit of zero words
+                            parse_state.add_note("No limit of compound words with value 0");
+                        }
                     } else {
                         parse_state.add_note("Expected number");
                     }
@@ -328,6 +387,84 @@ impl Parser {
         result
     }
 
+    /// Parses the keyboard layout in the format of KEY, the rows of keys separated by "|",
+    /// e.g. "qwertyuiop|asdfghjkl|zxcvbnm", into the keys left and right of each key.
+    /// A key in several rows has the neighbours from all of them.
+    pub fn parse_key_layout(layout: &str) -> HashMap<char, Vec<char>> {
+        let mut adjacency: HashMap<char, Vec<char>> = HashMap::default();
+        for row in layout.split('|') {
+            let keys: Vec<char> = row.chars().collect();
+            for pair in keys.windows(2) {
+                adjacency.entry(pair[0]).or_default().push(pair[1]);
+                adjacency.entry(pair[1]).or_default().push(pair[0]);
+            }
+        }
+        adjacency
+    }
+
+    /// Parses a PHONE rule: the characters, a group of characters in parentheses,
+    /// the "-" marks of the characters not replaced, the priority digits,
+    /// and the "^" and "$" anchors, e.g. "AH(AEIOUY)-^". The replacement "_" is empty.
+    pub fn parse_phone_rule(pattern: &str, replacement: &str) -> PhoneRule {
+        let mut phone_rule = PhoneRule {
+            phr_chars: vec![],
+            phr_group: vec![],
+            phr_ahead: 0,
+            phr_at_start: false,
+            phr_at_end: false,
+            phr_priority: 5,
+            phr_restart: false,
+            phr_replacement: if replacement == "_" { String::new() } else { replacement.to_string() },
+        };
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '(' => phone_rule.phr_group = chars.by_ref().take_while(|c| *c != ')').collect(),
+                '-' => phone_rule.phr_ahead += 1,
+                '^' => phone_rule.phr_at_start = true,
+                '$' => phone_rule.phr_at_end = true,
+                '<' => phone_rule.phr_restart = true,
+                c if c.is_ascii_digit() => phone_rule.phr_priority = c as u8 - b'0',
+                c => phone_rule.phr_chars.push(c),
+            }
+        }
+        phone_rule
+    }
+
+    /// Parses a member of MAP table, the characters and the parenthesized character sequences,
+    /// e.g. ["ss", "ß"] for "(ss)(ß)" or ["a", "á", "à"] for "aáà".
+    pub fn parse_map_group(map_entry: &str) -> Vec<String> {
+        let mut map_group: Vec<String> = vec![];
+        let mut chars = map_entry.chars();
+        while let Some(c) = chars.next() {
+            let member = if c == '(' {
+                chars.by_ref().take_while(|c| *c != ')').collect()
+            } else {
+                c.to_string()
+            };
+            if !member.is_empty() && !map_group.contains(&member) {
+                map_group.push(member);
+            }
+        }
+        map_group
+    }
+
+    /// Parses a hyphenation pattern of NEA HYPH into its letters and the levels of the positions
+    /// before, between and after them, e.g. ("hena", [0, 0, 0, 0, 4]) for "hena4".
+    pub fn parse_hyph_pattern(pattern: &str) -> (String, Vec<u8>) {
+        let mut letters = String::new();
+        let mut levels: Vec<u8> = vec![0];
+        for c in pattern.chars() {
+            if let Some(level) = c.to_digit(10) {
+                *levels.last_mut().unwrap() = level as u8;
+            } else {
+                letters.push(c);
+                levels.push(0);
+            }
+        }
+        (letters, levels)
+    }
+
     /// Parses the tag with an array of (String,String) values.
     /// If no errors, it updates "select_value".
     /// The "note" is set to Some if a message is to be issued.
@@ -347,20 +484,28 @@ impl Parser {
             if tag == parse_state.get_first_token() {
                 // REP 20 # replacement table
                 // REP ke que
+                // The count header is optional, a table can start with its only entry.
+                // Only a single number before any entry is taken as the header.
                 let tokens: Vec<&str> = parse_state.lps_tokens.collect();
-                let is_first = tokens.len() == 1 || tokens.len() >= 2 && tokens[1].starts_with("#");
-                if is_first {
-                    let group_size = tokens[0].parse::<u32>();
-                    if let Ok(group_size) = group_size {
-                        _ = variab.try_reserve(group_size as usize);
-                    }
+                if tokens.is_empty() {
+                    parse_state.add_note("Missing argument");
+                    return true;
+                }
+                let is_single = tokens.len() == 1 || tokens[1].starts_with("#");
+                let group_size = if is_single && variab.is_empty() {
+                    tokens[0].parse::<u32>().ok()
                 } else {
-                    if tokens.len() < 2 {
-                        parse_state.add_note("Not enough arguments, expected two");
-                    }
-                    if tokens.len() >= 2 {
-                        variab.push((tokens[0].to_string(), tokens[1].to_string()));
-                    }
+                    None
+                };
+                if let Some(group_size) = group_size {
+                    _ = variab.try_reserve(group_size as usize);
+                } else if is_single {
+                    parse_state.add_note("Not enough arguments, expected two");
+                } else if tag.ends_with("CONV") && tokens[0].contains('(') {
+                    // ICONV (ab)c x, only the anchors "_" are supported in conversions
+                    parse_state.add_note("Pattern groups are not supported in conversion, entry skipped");
+                } else {
+                    variab.push((tokens[0].to_string(), tokens[1].to_string()));
                     if tokens.len() > 2 && !tokens[2].starts_with("#") {
                         parse_state.add_note("Expected two arguments");
                     }
@@ -422,6 +567,10 @@ impl Parser {
             // SFX A Y 14
             let group_name = tokens[0].to_string();
             let can_circum = tokens[1] == "Y";
+            if tokens[1] != "Y" && tokens[1] != "N" {
+                // "y" or "Yes" would silently disable the combining of prefixes and suffixes
+                parse_state.add_note("Cross product in the PFX or SFX header should be Y or N");
+            }
             let group_size = tokens[2].parse::<u32>();
             if let Ok(group_size) = group_size {
                 let mut affix_group =
@@ -469,7 +618,7 @@ impl Parser {
             let mut affix_entry = AffixEntry::new(
                 sub.to_string(),
                 add,
-                Parser::parse_flags(&spell_lang, &next),
+                Parser::parse_flags(&spell_lang, &next, parse_state),
                 if tokens.len() < 4 {
                     "".to_string()
                 } else {
@@ -518,12 +667,8 @@ impl Parser {
     pub fn parse_aff_line(spell_lang: &mut SpellLang, mut parse_state: &mut LineParseState) {
         if parse_state.get_first_token() == "FLAG" {
             if let Some(flag_value) = parse_state.lps_tokens.next() {
-                if flag_value == "UTF-8" {
-                    spell_lang.slg_flag = FlagFormat::SingleUni;
-                } else if flag_value == "long" {
-                    spell_lang.slg_flag = FlagFormat::DoubleChar;
-                } else if flag_value == "num" {
-                    spell_lang.slg_flag = FlagFormat::Numeric;
+                if let Some(flag_format) = FlagFormat::from_tag_value(flag_value) {
+                    spell_lang.slg_flag = flag_format;
                 } else {
                     parse_state
                         .add_note("Unknown FLAG value, allowed are 'UTF-8', 'long', and 'num'");
@@ -538,15 +683,28 @@ impl Parser {
         } else if Parser::parse_number(spell_lang, &mut parse_state,) {
             // parsed, nothing more to do
         } else if Parser::parse_string_table(spell_lang, &mut parse_state) {
-            // parsed, nothing more to do
+            if parse_state.get_first_token() == "MAP" && spell_lang.slg_map.0.len() > spell_lang.slg_map_groups.len() {
+                let map_group = Parser::parse_map_group(spell_lang.slg_map.0.last().unwrap());
+                spell_lang.slg_map_groups.push(map_group);
+            }
         } else if Parser::parse_pair_table(spell_lang, &mut parse_state) {
             // parsed, nothing more to do
+        } else if parse_state.get_first_token() == "COMPOUNDSYLLABLE" {
+            // COMPOUNDSYLLABLE 6 aáeéiíoóöőuúüű
+            let tokens: Vec<&str> = parse_state.lps_tokens.collect();
+            if let (Some(Ok(syllable_max)), Some(vowels)) = (tokens.first().map(|token| token.parse::<u32>()), tokens.get(1)) {
+                spell_lang.slg_comp_syllable = (syllable_max, vowels.to_string());
+            } else {
+                parse_state.add_note("Expected number and vowels");
+            }
         } else if parse_state.get_first_token() == "COMPOUNDRULE" {
             // COMPOUNDRULE 4
             // COMPOUNDRULE 1np
             // COMPOUNDRULE mn*t
             let tokens: Vec<&str> = parse_state.lps_tokens.collect();
-            if !spell_lang.slg_compoundrule_parsed {
+            if tokens.is_empty() {
+                parse_state.add_note("Missing argument");
+            } else if !spell_lang.slg_compoundrule_parsed {
                 let group_size = tokens[0].parse::<u32>();
                 if let Ok(group_size) = group_size {
                     _ = spell_lang.slg_compoundrule.try_reserve(group_size as usize);
@@ -557,9 +715,8 @@ impl Parser {
                     parse_state.add_note("Expected one argument for COMPOUNDRULE");
                 }
                 let comp_rule_value: &str = tokens[0];
-                for comp_rule_flag in
-                Parser::parse_compoundrule_flags(&spell_lang, comp_rule_value)
-                {
+                let rule_items = Parser::parse_compoundrule(&spell_lang, comp_rule_value);
+                for (comp_rule_flag, _) in &rule_items {
                     spell_lang.slg_flag_hash.insert(
                         comp_rule_flag.clone(),
                         (
@@ -571,29 +728,11 @@ impl Parser {
                 spell_lang
                     .slg_compoundrule
                     .push(comp_rule_value.to_string());
+                spell_lang.slg_compoundrule_items.push(rule_items);
             }
         } else if Parser::parse_simple_flag(
             spell_lang,
-            &[
-                ("COMPOUNDFLAG", FlagType::FlagCompound),
-                ("COMPOUNDBEGIN", FlagType::FlagCompBegin),
-                ("COMPOUNDLAST", FlagType::FlagCompLast),
-                ("COMPOUNDMIDDLE", FlagType::FlagCompMid),
-                ("COMPOUNDEND", FlagType::FlagCompEnd),
-                ("ONLYINCOMPOUND", FlagType::FlagOnlyComp),
-                ("COMPOUNDPERMITFLAG", FlagType::FlagCompPermit),
-                ("COMPOUNDFORBIDFLAG", FlagType::FlagCompForbid),
-                ("COMPOUNDROOT", FlagType::FlagCompRoot),
-                ("NEEDAFFIX", FlagType::FlagNeedAffix),
-                ("CIRCUMFIX", FlagType::FlagCircumfix),
-                ("FORBIDDENWORD", FlagType::FlagForbidden),
-                ("SUBSTANDARD", FlagType::FlagSubstandard),
-                ("NOSUGGEST", FlagType::FlagNoSuggest),
-                ("KEEPCASE", FlagType::FlagKeepCase),
-                ("FORCEUCASE", FlagType::FlagForceUcase),
-                ("WARN", FlagType::FlagWarn),
-                ("LEMMA_PRESENT", FlagType::FlagLemma),
-            ],
+            &Parser::SIMPLE_FLAG_TAGS,
             &mut parse_state,
         ) {
         } else if parse_state.get_first_token() == "PFX" || parse_state.get_first_token() == "SFX" {
@@ -605,7 +744,9 @@ impl Parser {
             // AF TbTcff # 2
             // ...
             let tokens: Vec<&str> = parse_state.lps_tokens.collect();
-            if !spell_lang.slg_af_parsed {
+            if tokens.is_empty() {
+                parse_state.add_note("Missing argument");
+            } else if !spell_lang.slg_af_parsed {
                 let group_size = tokens[0].parse::<u32>();
                 if let Ok(group_size) = group_size {
                     _ = spell_lang.slg_af.try_reserve(group_size as usize);
@@ -638,11 +779,20 @@ impl Parser {
         }
     }
 
+    /// Returns true for a morphological field, two lowercase letters and colon
+    /// before the value, e.g. "ph:witch" or "st:walk".
+    fn is_morph_field(token: &str) -> bool {
+        let bytes = token.as_bytes();
+        bytes.len() > 3 && bytes[0].is_ascii_lowercase() && bytes[1].is_ascii_lowercase() && bytes[2] == b':'
+    }
+
+    /// The flags of 'ignore_flags' are removed from the words, without notes.
     pub fn parse_dic_entry(
         spell_lang: &mut SpellLang,
         dic_entry: &mut DicEntry,
         parse_state: &mut LineParseState,
         reporting_other: bool,
+        ignore_flags: &[String],
     ) {
         let flagged_words = dic_entry.den_source.split_whitespace();
         // the last slash starts flags, if not preceeded by backslash
@@ -653,7 +803,14 @@ impl Parser {
         // "ESP/Aprilia/BF" // todo report warning
         // "hab/km²/BF"
         // "km\/h"
+        // "which ph:witch", morphological fields after the words
         for flagged_word_str in flagged_words {
+            if !dic_entry.den_morph.is_empty()
+                || !dic_entry.den_words.is_empty() && Parser::is_morph_field(flagged_word_str)
+            {
+                dic_entry.den_morph.push(flagged_word_str.to_string());
+                continue;
+            }
             let slash_pos = flagged_word_str.rfind("/");
             if let Some(slash_pos) = slash_pos {
                 // if the previous character is backslash, again no flags are defined
@@ -670,12 +827,15 @@ impl Parser {
                         let from_slash = &flagged_word_str[slash_pos..];
                         let mut chars = from_slash.chars();
                         chars.next();
-                        let fwd_flags = chars.as_str();
+                        let fwd_flags = chars.as_str(); // empty for "nitidament/"
                         dic_entry.den_words.push(FlaggedWord::new(
                             before_slash,
-                            Parser::parse_flags(&spell_lang, &fwd_flags),
+                            Parser::parse_flags(&spell_lang, &fwd_flags, parse_state),
                         ));
                     }
+                } else if !dic_entry.den_words.is_empty() {
+                    // "word /ABC", a common editing mistake
+                    parse_state.add_note("Flags separated from the word by whitespace");
                 } else {
                     parse_state.add_note("Incorrect slash at the start of word");
                 }
@@ -685,6 +845,18 @@ impl Parser {
                     .push(FlaggedWord::new(flagged_word_str, vec![]));
             }
         }
+        if !ignore_flags.is_empty() {
+            // e.g. the flags of the tags that are not supported
+            for flagged_word in &mut dic_entry.den_words {
+                flagged_word.flw_flags.retain(|flag| !ignore_flags.contains(flag));
+            }
+        }
+        if !spell_lang.slg_ignore.is_empty() {
+            // the characters of IGNORE tag are removed from the words, as from the checked text
+            for flagged_word in &mut dic_entry.den_words {
+                flagged_word.flw_word.retain(|c| !spell_lang.slg_ignore.contains(c));
+            }
+        }
         for flagged_word in &dic_entry.den_words {
             for flag in &flagged_word.flw_flags {
                 let present = spell_lang.slg_flag_hash.contains_key(flag);
@@ -705,22 +877,44 @@ impl Parser {
         }
     }
 
-    pub fn parse_dictionary_count(spell_lang: &mut SpellLang, parse_state: &mut LineParseState) {
+    /// Parses the entry count at the start of dic file and reserves the memory for the entries.
+    /// Returns false if the memory could not be reserved, or the count is over 'reserve_max'.
+    /// Then the loading stops, or with 'continue_loading', it continues without reservation.
+    pub fn parse_dictionary_count(
+        spell_lang: &mut SpellLang,
+        parse_state: &mut LineParseState,
+        continue_loading: bool,
+        reserve_max: usize,
+    ) -> bool {
         // 57157
-        let group_size = parse_state.get_first_token().parse::<u32>();
+        // 57157 # entries
+        // a BOM can remain when the count follows SET or FLAG, or the files were concatenated
+        let mut reserved = true;
+        let group_size = parse_state.get_first_token().trim_start_matches('\u{feff}').parse::<u32>();
         if let Ok(group_size) = group_size {
-            let result = spell_lang.slg_dic_hash.try_reserve(group_size as usize);
-            if let Err(_result) = result {
-                parse_state.add_note("Not enough memory for dictionary");
-                // todo also prevent processing of the next lines
+            let result = if group_size as usize <= reserve_max {
+                spell_lang.slg_dic_hash.try_reserve(group_size as usize).is_ok()
+            } else {
+                false
+            };
+            if !result {
+                reserved = false;
+                if continue_loading {
+                    parse_state.add_note("Not enough memory for dictionary, loading without reservation");
+                } else {
+                    parse_state.add_note("Not enough memory for dictionary, loading stopped");
+                }
             }
             spell_lang.slg_dic_count = group_size;
         } else {
             parse_state.add_note("Entry count not recognized as number");
         }
-        if let Some(_) = parse_state.lps_tokens.next() {
-            parse_state.add_note("Unexpected argument after entry count");
+        if let Some(token) = parse_state.lps_tokens.next() {
+            if !token.starts_with('#') {
+                parse_state.add_note("Unexpected argument after entry count");
+            }
         }
+        reserved
     }
 
     /// The function returns up to 2 notes
@@ -728,21 +922,44 @@ impl Parser {
     /// .1 is detail, e.g. older definition being duplicated
     /// The line is without the initial comment and eol.
     /// Comments after the words, at the end of line, are still present.
+    /// Returns false for the line without a word, e.g. a comment.
     pub fn parse_dic_line(
         spell_lang: &mut SpellLang,
         parsed_line: &str,
         parse_state: &mut LineParseState,
         reporting_dupl: bool,
         reporting_other: bool,
-    ) {
+        ignore_flags: &[String],
+    ) -> bool {
         let mut dic_entry = DicEntry::new(parse_state.lps_line_no, parsed_line.to_string());
-        Parser::parse_dic_entry(spell_lang, &mut dic_entry, parse_state, reporting_other);
+        Parser::parse_dic_entry(spell_lang, &mut dic_entry, parse_state, reporting_other, ignore_flags);
         if dic_entry.den_words.len() == 0 {
             // empty or comment line
-            return;
+            return false;
         }
         let key = dic_entry.hash_key();
-        let existing_entry = spell_lang.slg_dic_hash.get_key_value(&key);
+        for morph_field in &dic_entry.den_morph {
+            if let Some(misspelling) = morph_field.strip_prefix("ph:") {
+                // the dictionary word as written, e.g. "Marseille ph:maarsayl"
+                let word_case = dic_entry.den_words[0].flw_char_case;
+                let (_, misspelling) = CharCase::normalize_case(misspelling);
+                spell_lang.slg_ph_hints.insert(misspelling, CharCase::restore_case(word_case, &key));
+            }
+        }
+        if let Some(existing_entry) = spell_lang.slg_dic_hash.get_mut(key.as_str()) {
+            if existing_entry.den_words.len() == 1
+                && dic_entry.den_words.len() == 1
+                && existing_entry
+                    .flagged_words()
+                    .all(|flagged_word| flagged_word.flw_char_case != dic_entry.den_words[0].flw_char_case)
+            {
+                // a homonym differing in case, e.g. "Polish" after "polish", both are kept
+                existing_entry.den_homonyms.push(dic_entry.den_words.remove(0));
+                existing_entry.den_homonym_morph.push(dic_entry.den_morph);
+                return true;
+            }
+        }
+        let existing_entry = spell_lang.slg_dic_hash.get_key_value(key.as_str());
         let mut description: Option<String> = None;
         let mut inserting_ok = true;
         if let Some(existing_entry) = existing_entry {
@@ -765,13 +982,14 @@ impl Parser {
             }
         }
         if inserting_ok {
-            spell_lang.slg_dic_hash.insert(key, dic_entry);
+            spell_lang.slg_dic_hash.insert(key.into(), dic_entry);
         }
         if let Some(note) = description {
             if reporting_dupl {
                 parse_state.add_note2("Duplicate entry", &note);
             }
         }
+        true
     }
 
     pub fn finalize_parsing(spell_lang: &mut SpellLang) -> Vec<String> {
@@ -787,7 +1005,7 @@ impl Parser {
             spell_lang.slg_affix_ct += affix_group.afc_affixes.len() as u32;
         }
         // set up prev_hash in order to initialize afg_prev_flags, calculated from afe_next_flags
-        let mut prev_hash: HashMap<u32, Vec<u32>> = HashMap::new(); // (key=next_ix, value=Vec<prev_ix>)
+        let mut prev_hash: HashMap<u32, Vec<u32>> = HashMap::default(); // (key=next_ix, value=Vec<prev_ix>)
         for affix_group in spell_lang.slg_aff_groups.iter_mut() {
             let mut flags_defined = false;
             let mut flags_uniform = true; // true when all afg_affixes members have the same afe_next_flags
@@ -827,6 +1045,13 @@ impl Parser {
                 ));
             }
         }
+        for cycle in Parser::find_continuation_cycles(spell_lang) {
+            let names: Vec<&str> = cycle
+                .iter()
+                .map(|ix| spell_lang.slg_aff_groups[*ix as usize].afc_name.as_str())
+                .collect();
+            notes.push(format!("Continuation flags form a cycle: {}", names.join(" -> ")));
+        }
         for (next_ix, prev_vec) in prev_hash {
             let affix_group = &mut spell_lang.slg_aff_groups[next_ix as usize];
             affix_group.afc_prev_flags = prev_vec;
@@ -841,38 +1066,134 @@ impl Parser {
         notes
     }
 
+    /// Returns the cycles of the affix groups by the continuation flags, e.g. [A, B, A]
+    /// when an affix of group A has continuation flag B and an affix of group B has flag A.
+    /// Each cycle is found by depth-first search as a path returning to a group on the path.
+    pub fn find_continuation_cycles(spell_lang: &SpellLang) -> Vec<Vec<u32>> {
+        let next_ixes: Vec<Vec<u32>> = spell_lang
+            .slg_aff_groups
+            .iter()
+            .map(|affix_group| {
+                let mut ixes: Vec<u32> = vec![];
+                for affix_entry in &affix_group.afc_affixes {
+                    for next_flag in &affix_entry.afe_next_flags {
+                        if let Some((FlagType::FlagAffix, next_ix)) = spell_lang.slg_flag_hash.get(next_flag) {
+                            if !ixes.contains(next_ix) {
+                                ixes.push(*next_ix);
+                            }
+                        }
+                    }
+                }
+                ixes
+            })
+            .collect();
+        // 0: not visited, 1: on the current path, 2: finished
+        let mut states = vec![0u8; next_ixes.len()];
+        let mut cycles: Vec<Vec<u32>> = vec![];
+        for start_ix in 0..next_ixes.len() {
+            if states[start_ix] != 0 {
+                continue;
+            }
+            // the path with the index of the next successor to visit
+            let mut path: Vec<(u32, usize)> = vec![(start_ix as u32, 0)];
+            states[start_ix] = 1;
+            while let Some((group_ix, next_pos)) = path.last_mut() {
+                let group_ix = *group_ix as usize;
+                if let Some(next_ix) = next_ixes[group_ix].get(*next_pos).copied() {
+                    *next_pos += 1;
+                    match states[next_ix as usize] {
+                        0 => {
+                            states[next_ix as usize] = 1;
+                            path.push((next_ix, 0));
+                        }
+                        1 => {
+                            let cycle_start = path.iter().position(|(ix, _)| *ix == next_ix).unwrap();
+                            let mut cycle: Vec<u32> = path[cycle_start..].iter().map(|(ix, _)| *ix).collect();
+                            cycle.push(next_ix);
+                            cycles.push(cycle);
+                        }
+                        _ => {}
+                    }
+                } else {
+                    states[group_ix] = 2;
+                    path.pop();
+                }
+            }
+        }
+        cycles
+    }
+
+    /// Checks the affix entries for problems that are not found while parsing:
+    /// the conditions that are never satisfied and the entries repeated in their class.
+    /// Returns the descriptions of the problems.
+    pub fn validate_affixes(spell_lang: &SpellLang) -> Vec<String> {
+        let mut notes: Vec<String> = vec![];
+        for affix_group in &spell_lang.slg_aff_groups {
+            for (entry_ix, affix_entry) in affix_group.afc_affixes.iter().enumerate() {
+                let is_duplicate = affix_group.afc_affixes[..entry_ix].iter().any(|prev_entry| {
+                    prev_entry.afe_sub == affix_entry.afe_sub
+                        && prev_entry.afe_add == affix_entry.afe_add
+                        && prev_entry.afe_next_flags == affix_entry.afe_next_flags
+                        && prev_entry.afe_cond.rgx_def == affix_entry.afe_cond.rgx_def
+                });
+                // the empty strip and add are written as 0, as in the aff file
+                let sub = if affix_entry.afe_sub.is_empty() { "0" } else { &affix_entry.afe_sub };
+                let add = if affix_entry.afe_add.is_empty() { "0" } else { &affix_entry.afe_add };
+                if is_duplicate {
+                    notes.push(format!(
+                        "Duplicate entry in {} {}: strip {}, add {}, condition {}",
+                        if affix_group.afc_is_pre { "PFX" } else { "SFX" },
+                        affix_group.afc_name,
+                        sub,
+                        add,
+                        affix_entry.afe_cond
+                    ));
+                }
+                if !affix_entry.condition_fits_strip(affix_group.afc_is_pre) {
+                    notes.push(format!(
+                        "Condition never satisfied in {} {}: strip {}, add {}, condition {}",
+                        if affix_group.afc_is_pre { "PFX" } else { "SFX" },
+                        affix_group.afc_name,
+                        sub,
+                        add,
+                        affix_entry.afe_cond
+                    ));
+                }
+            }
+        }
+        notes
+    }
+
     pub fn get_summary(spell_lang: &SpellLang) -> String {
-        let mut noparse_tags = String::from("");
-        let mut first_tag = true;
-        for (key, value) in &spell_lang.slg_noparse_tags {
-            noparse_tags += if first_tag { ", other tags " } else { "," };
-            noparse_tags += key;
-            noparse_tags.push('*');
-            noparse_tags += &value.to_string();
-            first_tag = false;
-        }
-        let mut noparse_flags = String::from("");
-        let mut first_flag = true;
-        for (key, value) in &spell_lang.slg_noparse_flags {
-            noparse_flags += if first_flag { ", other flags " } else { "," };
-            noparse_flags += key;
-            noparse_flags.push('*');
-            noparse_flags += &value.to_string();
-            first_flag = false;
-        }
-        let duplicated = if spell_lang.slg_dic_duplicated != 0 {
-            format!(", {} duplicated entries", spell_lang.slg_dic_duplicated)
+        spell_lang.summary().to_string()
+    }
+}
+
+/// The results of the dictionary tests, see TextParser::run_tests.
+#[derive(Debug)]
+pub struct TestReport {
+    pub trp_passed: u32,
+    pub trp_failed: u32,
+    /// the good words that were rejected
+    pub trp_rejected_good: Vec<String>,
+    /// the wrong words that were accepted
+    pub trp_accepted_wrong: Vec<String>,
+    /// with the TESTBADGRAM section, true if it had the expected parse notes
+    pub trp_bad_gram_passed: Option<bool>,
+}
+
+impl TestReport {
+    fn add_result(&mut self, passed: bool) {
+        if passed {
+            self.trp_passed += 1;
         } else {
-            String::from("")
-        };
-        let summary = format!(
-            "encoding {}, affixes {}/{}, word entries {}{duplicated}{noparse_tags}{noparse_flags}.",
-            spell_lang.slg_set,
-            spell_lang.slg_flag_hash.len(),
-            spell_lang.slg_affix_ct,
-            spell_lang.slg_dic_hash.len(),
-        );
-        summary
+            self.trp_failed += 1;
+        }
+    }
+
+    /// True when no test failed.
+    pub fn all_passed(&self) -> bool {
+        self.trp_failed == 0
     }
 }
 
@@ -1031,9 +1352,38 @@ pub struct TextParser {
     /// Used for compatible processing, to have external test parity.
     /// There will be perhaps more spelling modes in the future.
     pub tps_mode_flags: u32,
+    /// combined CheckFeature values of the enabled features, copied to the loaded languages,
+    /// see option --features
+    pub tps_features: u32,
     pub tps_langs: Vec<SpellLang>,
     /// maximal number of notes
     pub tps_max_notes: u32,
+    /// flag: report the problems found by additional checks of the dictionary
+    pub tps_validating: bool,
+    /// flag: with tps_validating, stop loading at the first unknown tag
+    pub tps_strict: bool,
+    /// set when loading was stopped in strict validation
+    pub tps_strict_failed: bool,
+    /// flag: when the memory for dictionary can't be reserved, continue loading without reservation
+    pub tps_continue_loading: bool,
+    /// the largest entry count of a dic file reserved in advance, the larger counts
+    /// are handled as if the memory could not be reserved; by default unlimited
+    pub tps_reserve_max: usize,
+    /// maximal number of suggestions for a word, copied to the loaded languages,
+    /// see option --suggest-max
+    pub tps_suggest_max: u32,
+    /// maximal length of a checked word in characters, copied to the loaded languages,
+    /// by default 100, see option --max-word-length
+    pub tps_word_len_max: usize,
+    /// patterns of the tokens that aren't checked, copied to the loaded languages
+    pub tps_skip_patterns: Vec<SkipPattern>,
+    /// name of the built-in keyboard layout for the suggestions, copied to the loaded languages
+    pub tps_keyboard: String,
+    /// format of the flags in the dictionaries without FLAG tag, copied to the loaded languages,
+    /// by default single characters
+    pub tps_default_flag: FlagFormat,
+    /// the flags removed from the dictionary words, see option --ignore-flags
+    pub tps_ignore_flags: Vec<String>,
     pub tps_warn: HashSet<&'static str>,
     pub tps_line_notes: Vec<String>,
 
@@ -1076,9 +1426,21 @@ impl TextParser {
             tps_skip_output: false,
             tps_showing_details: false,
             tps_mode_flags: 0,
+            tps_features: CheckFeature::ALL,
             tps_langs: vec![],
             tps_max_notes: 10,
-            tps_warn: HashSet::new(),
+            tps_validating: false,
+            tps_strict: false,
+            tps_strict_failed: false,
+            tps_continue_loading: false,
+            tps_reserve_max: usize::MAX,
+            tps_suggest_max: 5,
+            tps_word_len_max: 100,
+            tps_skip_patterns: vec![],
+            tps_keyboard: String::new(),
+            tps_default_flag: FlagFormat::SingleChar,
+            tps_ignore_flags: vec![],
+            tps_warn: HashSet::default(),
             tps_line_notes: vec![],
 
             tps_parse_status: ParseStatus::FileEnded,
@@ -1198,6 +1560,24 @@ impl TextParser {
         }
     }
 
+    /// Stores a note for each repeated note, with the number of occurrences,
+    /// e.g. "Unknown flag: X (1234 occurrences)".
+    fn store_repeated_notes(&mut self, file_code: &str, file_ext: &str, note_repeats: NoteRepeats) {
+        let mut repeated: Vec<(usize, u32, &'static str, Option<String>)> = note_repeats
+            .into_iter()
+            .filter(|(_, (_, note_ct))| *note_ct > 1)
+            .map(|((desc, details), (order, note_ct))| (order, note_ct, desc, details))
+            .collect();
+        repeated.sort_by_key(|note| note.0);
+        for (_, note_ct, desc, details) in repeated {
+            let desc = match details {
+                Some(details) => format!("{desc}: {details} ({note_ct} occurrences)"),
+                None => format!("{desc} ({note_ct} occurrences)"),
+            };
+            self.store_noline_note(file_code, file_ext, &desc);
+        }
+    }
+
     fn store_summary_note(
         &mut self,
         extension: &str,
@@ -1244,6 +1624,8 @@ impl TextParser {
         }
     }
 
+    /// Stores the notes of the line. A note repeating an earlier one of the file
+    /// (the same description and details) is only counted in 'note_repeats'.
     fn store_line_notes(
         &mut self,
         file_code: &str,
@@ -1251,19 +1633,41 @@ impl TextParser {
         parse_state: &LineParseState,
         line_as_string: &String,
         note_count: &mut u32,
+        note_repeats: &mut NoteRepeats,
     ) {
         for parse_note in parse_state.get_notes() {
-            if *note_count < self.tps_max_notes {
+            *note_count += 1;
+            let note_key = (parse_note.psn_desc, parse_note.psn_details.clone());
+            let unique_count = note_repeats.len() as u32; // the different notes before this one
+            let (_, note_ct) = note_repeats.entry(note_key).or_insert((unique_count as usize, 0));
+            *note_ct += 1;
+            if *note_ct > 1 {
+                continue;
+            }
+            if unique_count < self.tps_max_notes {
                 self.store_parse_note(&file_code, file_ext, &line_as_string, &parse_note);
-            } else if *note_count == self.tps_max_notes {
+            } else if unique_count == self.tps_max_notes {
                 self.store_noline_note(&file_code, file_ext, "Next parse errors not shown");
             }
-            *note_count += 1;
         }
     }
 
     pub fn finalize_description_part(&mut self, spell_lang: &mut SpellLang, file_ext: &str) {
         let notes = Parser::finalize_parsing(spell_lang);
+        if self.tps_validating {
+            for note in Parser::validate_affixes(spell_lang) {
+                self.store_note(&format!("{}.{}: {}", spell_lang.slg_code, file_ext, note));
+            }
+            let mut unsupported: Vec<&String> = spell_lang
+                .slg_noparse_tags
+                .keys()
+                .filter(|tag| Parser::KNOWN_TAGS.contains(&tag.as_str()) && tag.as_str() != "SET")
+                .collect();
+            unsupported.sort();
+            for tag in unsupported {
+                self.store_note(&format!("{}.{}: Tag not supported: {}", spell_lang.slg_code, file_ext, tag));
+            }
+        }
         if self.tps_showing_details {
             let mut note_count = 0;
             for note in notes {
@@ -1282,13 +1686,46 @@ impl TextParser {
         }
     }
 
-    pub fn parse_nea_token(parse_lang: &mut TextParser, parse_state: &mut LineParseState) -> ParseMode {
+    pub fn parse_nea_token(
+        parse_lang: &mut TextParser,
+        spell_lang: &mut SpellLang,
+        parse_state: &mut LineParseState,
+    ) -> ParseMode {
         // NEA DIC {
         // NEA TESTBADGRAM {
         // NEA TESTGOODWORDS {
         // NEA TESTBADWORDS {
+        // NEA COMPOUNDLINK s es
+        // NEA SOFTPREFIX USB-
+        // NEA SOFTSUFFIX GB
+        // NEA HYPH hy3ph he2n
         let mut next_mode = ParseMode::Toplevel;
         if let Some(nea2) = parse_state.get_next_token() {
+            let parsing_hyph = nea2 == "HYPH";
+            let nea_list = match nea2 {
+                "COMPOUNDLINK" => Some((&mut spell_lang.slg_comp_linkers, "Expected linking elements after COMPOUNDLINK")),
+                "SOFTPREFIX" => Some((&mut spell_lang.slg_soft_prefixes, "Expected soft breaks after SOFTPREFIX")),
+                "SOFTSUFFIX" => Some((&mut spell_lang.slg_soft_suffixes, "Expected soft breaks after SOFTSUFFIX")),
+                "HYPH" => Some((&mut spell_lang.slg_hyph, "Expected patterns after HYPH")),
+                _ => None,
+            };
+            if let Some((nea_list, missing_desc)) = nea_list {
+                // the linking elements or the soft breaks, on the same line
+                let old_len = nea_list.len();
+                while let Some(item) = parse_state.get_next_token() {
+                    nea_list.push(item.to_string());
+                }
+                if nea_list.len() == old_len {
+                    parse_state.add_note(missing_desc);
+                }
+                if parsing_hyph {
+                    for pattern in &spell_lang.slg_hyph[old_len..] {
+                        let (letters, levels) = Parser::parse_hyph_pattern(pattern);
+                        spell_lang.slg_hyph_patterns.insert(letters, levels);
+                    }
+                }
+                return next_mode;
+            }
             if nea2 == "DIC" {
                 next_mode = ParseMode::WordDic;
             } else if nea2 == "TESTBADGRAM" {
@@ -1316,6 +1753,106 @@ impl TextParser {
         next_mode
     }
 
+    /// Loads the dictionary from the aff and dic text and checks the test words
+    /// from the good and wrong text, one word per line, see TestReport.
+    /// The mode flags of the parser are used, e.g. ModeFlag::TestCompat.
+    pub fn run_tests(
+        &mut self,
+        aff: impl LineReader,
+        dic: impl LineReader,
+        mut good: impl LineReader,
+        mut wrong: impl LineReader,
+    ) -> TestReport {
+        let mut spell_lang = self.parse_lang_readers(aff, dic);
+        self.parse_dictionary_text(&mut spell_lang, &mut good);
+        self.parse_dictionary_text(&mut spell_lang, &mut wrong);
+        self.test_report(&spell_lang)
+    }
+
+    /// Loads the neadic text with the test sections and checks the test words, see TestReport.
+    pub fn run_neadic_tests(&mut self, mut neadic: impl LineReader) -> TestReport {
+        let mut spell_lang = self.new_spell_lang(&neadic.get_base_name());
+        self.parse_dictionary_text(&mut spell_lang, &mut neadic);
+        self.test_report(&spell_lang)
+    }
+
+    /// Checks the test words loaded from the good and wrong words (or the neadic test sections).
+    pub fn test_report(&self, spell_lang: &SpellLang) -> TestReport {
+        let mut test_report = TestReport {
+            trp_passed: 0,
+            trp_failed: 0,
+            trp_rejected_good: vec![],
+            trp_accepted_wrong: vec![],
+            trp_bad_gram_passed: None,
+        };
+        if self.tps_testing_bad_gram {
+            test_report.add_result(self.tps_test_bad_gram_passed);
+            test_report.trp_bad_gram_passed = Some(self.tps_test_bad_gram_passed);
+        }
+        for word in self.tps_test_good_words.iter().filter(|word| !word.is_empty()) {
+            let passed = Spell::check_token(spell_lang, word);
+            test_report.add_result(passed);
+            if !passed {
+                test_report.trp_rejected_good.push(word.clone());
+            }
+        }
+        for word in self.tps_test_bad_words.iter().filter(|word| !word.is_empty()) {
+            let passed = !Spell::check_token(spell_lang, word);
+            test_report.add_result(passed);
+            if !passed {
+                test_report.trp_accepted_wrong.push(word.clone());
+            }
+        }
+        test_report
+    }
+
+    /// Returns an empty language with the settings of the parser, e.g. tps_features
+    /// and tps_skip_patterns, copied to the slg_ fields. The dictionary files are loaded into it.
+    pub fn new_spell_lang(&self, slg_code: &str) -> SpellLang {
+        let mut spell_lang = SpellLang::new(slg_code);
+        spell_lang.slg_mode_flags = self.tps_mode_flags;
+        spell_lang.set_features(self.tps_features);
+        spell_lang.slg_suggest_max = self.tps_suggest_max;
+        spell_lang.slg_word_len_max = self.tps_word_len_max;
+        spell_lang.slg_skip_patterns = self.tps_skip_patterns.clone();
+        spell_lang.slg_keyboard = self.tps_keyboard.clone();
+        spell_lang.slg_flag = self.tps_default_flag;
+        spell_lang
+    }
+
+    /// Loads the dictionary from exactly two sources, the aff and the dic text,
+    /// without accessing the file system. The language code is the base name of 'aff'.
+    /// The notes are kept in tps_line_notes.
+    pub fn parse_lang_readers(
+        &mut self,
+        mut aff: impl LineReader,
+        mut dic: impl LineReader,
+    ) -> SpellLang {
+        let mut spell_lang = self.new_spell_lang(&aff.get_base_name());
+        self.parse_dictionary_text(&mut spell_lang, &mut aff);
+        self.parse_dictionary_text(&mut spell_lang, &mut dic);
+        spell_lang
+    }
+
+    /// Parses a dic file that has no aff file of its own (e.g. de_med)
+    /// as a supplement to the already loaded 'spell_lang'.
+    /// The words are added to 'spell_lang' and use its affixes and flags.
+    pub fn parse_supplement_dic(
+        &mut self,
+        spell_lang: &mut SpellLang,
+        line_reader: &mut impl LineReader,
+    ) {
+        let base_dic_count = spell_lang.slg_dic_count;
+        let base_set = spell_lang.slg_set.clone();
+        let base_flag = spell_lang.slg_flag;
+        spell_lang.slg_dic_count = 0; // the supplement starts with its own entry count
+        self.parse_dictionary_text(spell_lang, line_reader);
+        spell_lang.slg_dic_count += base_dic_count;
+        // SET or FLAG in the supplement only apply to its own lines
+        spell_lang.slg_set = base_set;
+        spell_lang.slg_flag = base_flag;
+    }
+
     /// The function parses the one file of language definition
     /// in text form and returns a vector of notes (mostly with problems)
     pub fn parse_dictionary_text(
@@ -1340,11 +1877,16 @@ impl TextParser {
         );
         let mut line_no = 0;
         let mut note_count: u32 = 0;
+        let mut note_repeats: NoteRepeats = HashMap::default();
         let bad_encoding: u32 = 0;
         let reporting_dupl = self.tps_warn.contains(Self::SHOW_DUPLICATES);
         let reporting_other = self.tps_warn.contains(Self::SHOW_DIC_OTHER);
         let orig_parse_mode = parse_mode; // for the whole file
         let mut finalized = false;
+        let mut stop_loading = false;
+        // the indexes are rebuilt only after the files adding words, not after .good or .wrong
+        let key_count_before = spell_lang.slg_dic_hash.len();
+        let mut dic_word_lines: u32 = 0; // in the .dic file or in the current NEA DIC section
         loop {
             let parse_mode_before_line = parse_mode;
             line_no += 1;
@@ -1367,6 +1909,14 @@ impl TextParser {
                         self.tps_test_bad_gram_passed = false;
                     }
                 }
+                if parse_mode == ParseMode::WordDic && dic_word_lines == 0 {
+                    // all words would be reported as wrong, shown also without details
+                    self.store_note(&format!(
+                        "{}.{}:{}: No words loaded from the NEA DIC section",
+                        spell_lang.slg_code, file_ext, line_no
+                    ));
+                }
+                dic_word_lines = 0;
                 parse_mode = ParseMode::Toplevel;
                 self.tps_mode_until_brace = false;
                 // todo check no more tokens
@@ -1375,21 +1925,49 @@ impl TextParser {
                     Self::parse_charset(spell_lang, &mut parse_state);
                 }
                 if parse_state.get_first_token() == "NEA" {
-                    parse_mode = TextParser::parse_nea_token(self, &mut parse_state);
+                    parse_mode = TextParser::parse_nea_token(self, spell_lang, &mut parse_state);
+                } else {
+                    Parser::parse_aff_line(spell_lang, &mut parse_state);
+                    if self.tps_strict && Parser::is_unknown_tag(spell_lang, parse_state.get_first_token()) {
+                        // fail fast, shown also without details
+                        self.store_note(&format!(
+                            "{}.{}:{}: Unknown tag, loading stopped: {}",
+                            spell_lang.slg_code, file_ext, line_no, parsed_line
+                        ));
+                        self.tps_strict_failed = true;
+                        stop_loading = true;
+                    }
+                }
+            } else if orig_parse_mode == ParseMode::WordDic
+                && spell_lang.slg_dic_count == 0
+                && (parse_state.get_first_token() == "SET" || parse_state.get_first_token() == "FLAG")
+            {
+                // .dic file without .aff file, the encoding or flag format before the count
+                if parse_state.get_first_token() == "SET" {
+                    Self::parse_charset(spell_lang, &mut parse_state);
                 } else {
                     Parser::parse_aff_line(spell_lang, &mut parse_state);
                 }
             } else if orig_parse_mode == ParseMode::WordDic && spell_lang.slg_dic_count == 0 {
                 // .dic file, 1st line
-                Parser::parse_dictionary_count(spell_lang, &mut parse_state);
+                let reserved = Parser::parse_dictionary_count(
+                    spell_lang,
+                    &mut parse_state,
+                    self.tps_continue_loading,
+                    self.tps_reserve_max,
+                );
+                stop_loading = !reserved && !self.tps_continue_loading;
             } else if parse_mode == ParseMode::WordDic {
-                Parser::parse_dic_line(
+                if Parser::parse_dic_line(
                     spell_lang,
                     &self.tps_parsed_line,
                     &mut parse_state,
                     reporting_dupl,
                     reporting_other,
-                );
+                    &self.tps_ignore_flags,
+                ) {
+                    dic_word_lines += 1;
+                }
             } else if parse_mode == ParseMode::TestGoodWords {
                 self.tps_test_good_words.push(parse_state.get_first_token().to_string());
             } else if parse_mode == ParseMode::TestBadWords {
@@ -1401,6 +1979,7 @@ impl TextParser {
                 &mut parse_state,
                 &parsed_line,
                 &mut note_count,
+                &mut note_repeats,
             );
             if orig_parse_mode == ParseMode::Toplevel
                 && parse_mode_before_line != parse_mode
@@ -1410,11 +1989,1386 @@ impl TextParser {
                 finalized = true;
             }
             self.tps_total_notes += parse_state.get_note_length();
+            if stop_loading {
+                break;
+            }
         }
         if orig_parse_mode == ParseMode::Toplevel && parse_mode == ParseMode::Toplevel && !finalized
         {
             self.finalize_description_part(spell_lang, file_ext);
         }
+        if spell_lang.slg_dic_hash.len() != key_count_before {
+            spell_lang.index_words();
+        }
+        if orig_parse_mode == ParseMode::WordDic && dic_word_lines == 0 {
+            // all words would be reported as wrong, or no words added by a supplement dic,
+            // shown also without details
+            self.store_note(&format!(
+                "{}.{}: No words loaded from the dictionary",
+                spell_lang.slg_code, file_ext
+            ));
+        }
+        self.store_repeated_notes(&spell_lang.slg_code, file_ext, note_repeats);
         self.store_summary_note(file_ext, &spell_lang.slg_code, bad_encoding, note_count);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::core_speller::{
+        AffixUsage, CharCase, CharClass, CheckFeature, FlagFormat, FlagType, Issue, IssueKind, ModeFlag, PhoneRule, RejectionReason, Spell,
+        SkipPattern, SpellLang, TokenType, WordCache,
+    };
+    use crate::text_parser::{LineReader, Parser, TextParser};
+
+    /// Reads the lines of a dictionary given as a string,
+    /// or as bytes in other encodings than UTF-8.
+    struct StrLineReader {
+        srd_extension: String,
+        srd_lines: Vec<Vec<u8>>,
+        srd_next_ix: usize,
+    }
+
+    impl StrLineReader {
+        fn new(srd_extension: &str, text: &str) -> StrLineReader {
+            StrLineReader {
+                srd_extension: srd_extension.to_string(),
+                srd_lines: text.lines().map(|line| (line.to_string() + "\n").into_bytes()).collect(),
+                srd_next_ix: 0,
+            }
+        }
+
+        fn from_bytes(srd_extension: &str, bytes: &[u8]) -> StrLineReader {
+            StrLineReader {
+                srd_extension: srd_extension.to_string(),
+                srd_lines: bytes.split_inclusive(|b| *b == b'\n').map(|line| line.to_vec()).collect(),
+                srd_next_ix: 0,
+            }
+        }
+    }
+
+    impl LineReader for StrLineReader {
+        fn get_base_name(&self) -> String {
+            String::from("test")
+        }
+        fn get_extension(&self) -> String {
+            self.srd_extension.clone()
+        }
+        fn read_line(&mut self) -> Option<Vec<u8>> {
+            let line = self.srd_lines.get(self.srd_next_ix)?;
+            self.srd_next_ix += 1;
+            Some(line.clone())
+        }
+    }
+
+    /// Parses a dictionary in the neadic format, with the notes shown.
+    fn parse_neadic(text: &str) -> (TextParser, SpellLang) {
+        parse_lang("test", text)
+    }
+
+    fn parse_lang(lang_code: &str, text: &str) -> (TextParser, SpellLang) {
+        let mut text_parser = TextParser::new();
+        text_parser.tps_showing_details = true;
+        let mut spell_lang = text_parser.new_spell_lang(lang_code);
+        let mut line_reader = StrLineReader::new(TextParser::EXT_NEADIC, text);
+        text_parser.parse_dictionary_text(&mut spell_lang, &mut line_reader);
+        (text_parser, spell_lang)
+    }
+
+    #[test]
+    fn pair_table_single_entry_test() {
+        let (_, spell_lang) = parse_neadic("REP ke que\nICONV 1\nICONV a b\n");
+        assert_eq!(spell_lang.slg_rep, vec![("ke".to_string(), "que".to_string())]);
+        assert_eq!(spell_lang.slg_iconv, vec![("a".to_string(), "b".to_string())]);
+        let (text_parser, spell_lang) = parse_neadic("REP 1\nREP ke\n");
+        assert!(spell_lang.slg_rep.is_empty());
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("expected two")));
+    }
+
+    #[test]
+    fn long_flags_odd_length_test() {
+        let (text_parser, spell_lang) =
+            parse_neadic("FLAG long\nSFX G1 Y 1\nSFX G1 e ing e\nNEA DIC {\n    game/G1X\n}\n");
+        let dic_entry = spell_lang.slg_dic_hash.get("game").unwrap();
+        assert_eq!(dic_entry.den_words[0].flw_flags, vec!["G1".to_string()]);
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("Odd number")));
+    }
+
+    #[test]
+    fn iconv_applied_test() {
+        let (text_parser, spell_lang) =
+            parse_neadic("ICONV 2\nICONV oe œ\nICONV (o)e x\nNEA DIC {\n    cœur\n}\n");
+        assert_eq!(spell_lang.slg_iconv.len(), 1);
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("not supported")));
+        assert!(Spell::check_token(&spell_lang, "coeur"));
+        assert!(Spell::check_token(&spell_lang, "cœur"));
+        assert!(!Spell::check_token(&spell_lang, "cour"));
+    }
+
+    #[test]
+    fn stems_test() {
+        let (_, spell_lang) =
+            parse_neadic("NEA DIC {\n    word\n    game/G\n    buena/B tarde/B\n    England\n}\n");
+        let mut stems: Vec<&str> = spell_lang.stems().collect();
+        stems.sort();
+        assert_eq!(stems, vec!["england", "game", "word"]);
+    }
+
+    #[test]
+    fn empty_flags_after_slash_test() {
+        let (_, spell_lang) = parse_neadic("NEA DIC {\n    nitidament/ \n    word/\n}\n");
+        let dic_entry = spell_lang.slg_dic_hash.get("word").unwrap();
+        assert_eq!(dic_entry.den_words.len(), 1);
+        assert!(dic_entry.den_words[0].flw_flags.is_empty());
+        assert!(!spell_lang.slg_dic_hash.contains_key("word/"));
+        assert!(Spell::check_token(&spell_lang, "word"));
+        assert!(Spell::check_token(&spell_lang, "nitidament"));
+    }
+
+    #[test]
+    fn check_text_langs_test() {
+        let (_, base_lang) = parse_lang("de", "NEA DIC {\n    Haus\n    und\n}\n");
+        let (_, med_lang) = parse_lang("de_med", "NEA DIC {\n    Tibia\n    und\n}\n");
+        let spell_langs = vec![base_lang, med_lang];
+        assert_eq!(Spell::check_token_langs(&spell_langs, "und"), Some("de"));
+        let checked = Spell::check_text_langs(&spell_langs, "Haus und Tibia, Fibula");
+        let words: Vec<(&str, TokenType, Option<&str>)> = checked
+            .iter()
+            .filter(|token| token.1 != TokenType::NotWord)
+            .map(|token| (token.0.as_str(), token.1, token.2))
+            .collect();
+        assert_eq!(
+            words,
+            vec![
+                ("Haus", TokenType::IsGoodWord, Some("de")),
+                ("und", TokenType::IsGoodWord, Some("de")),
+                ("Tibia", TokenType::IsGoodWord, Some("de_med")),
+                ("Fibula", TokenType::IsBadWord, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_text_guess_langs_test() {
+        let (_, en_lang) = parse_lang("en", "NEA DIC {\n    the\n    cat\n    sleeps\n    here\n}\n");
+        let (_, es_lang) = parse_lang("es", "NEA DIC {\n    el\n    gato\n    duerme\n}\n");
+        let spell_langs = vec![en_lang, es_lang];
+        let checked = Spell::check_text_guess_langs(&spell_langs, "The cat sleeps el. El gato duerme here.");
+        let words: Vec<(&str, TokenType, Option<&str>)> = checked
+            .iter()
+            .filter(|token| token.1 != TokenType::NotWord)
+            .map(|token| (token.0.as_str(), token.1, token.2))
+            .collect();
+        assert_eq!(
+            words,
+            vec![
+                ("The", TokenType::IsGoodWord, Some("en")),
+                ("cat", TokenType::IsGoodWord, Some("en")),
+                ("sleeps", TokenType::IsGoodWord, Some("en")),
+                ("el", TokenType::IsBadWord, Some("en")),
+                ("El", TokenType::IsGoodWord, Some("es")),
+                ("gato", TokenType::IsGoodWord, Some("es")),
+                ("duerme", TokenType::IsGoodWord, Some("es")),
+                ("here", TokenType::IsBadWord, Some("es")),
+            ]
+        );
+    }
+
+    #[test]
+    fn supplement_dic_test() {
+        let (mut text_parser, mut spell_lang) =
+            parse_lang("de", "SFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    Haus/S\n}\n");
+        let mut line_reader = StrLineReader::new(TextParser::EXT_DIC, "2\nTibia/S\nFemur\n");
+        text_parser.parse_supplement_dic(&mut spell_lang, &mut line_reader);
+        assert!(Spell::check_token(&spell_lang, "Haus"));
+        assert!(Spell::check_token(&spell_lang, "Tibias"));
+        assert!(Spell::check_token(&spell_lang, "Femur"));
+        assert!(!spell_lang.slg_dic_hash.contains_key("2"));
+    }
+
+    #[test]
+    fn check_text_spans_ignore_test() {
+        let (_, spell_lang) = parse_neadic("IGNORE \u{ad}\nNEA DIC {\n    spelling\n}\n");
+        let text = "a spel\u{ad}ling";
+        let spans = Spell::check_text_spans(&spell_lang, text);
+        let (span, token_type) = spans.last().unwrap();
+        assert_eq!(&text[span.clone()], "spel\u{ad}ling");
+        assert!(*token_type == TokenType::IsGoodWord);
+    }
+
+    #[test]
+    fn stems_with_flag_test() {
+        let (_, spell_lang) = parse_neadic(
+            "FLAG long\nNOSUGGEST Ns\nAF 2\nAF NsG1\nAF G1\nNEA DIC {\n    one/Ns\n    two/1\n    three/2\n    four\n}\n",
+        );
+        let mut stems: Vec<&str> = spell_lang.stems_with_flag("Ns").collect();
+        stems.sort();
+        assert_eq!(stems, vec!["one", "two"]);
+    }
+
+    #[test]
+    fn detached_flags_test() {
+        let (text_parser, _) = parse_neadic("NEA DIC {\n    word /ABC\n    /DEF\n}\n");
+        let notes = &text_parser.tps_line_notes;
+        assert!(notes.iter().any(|note| note.contains(":2: Flags separated from the word")));
+        assert!(notes.iter().any(|note| note.contains(":3: Incorrect slash at the start")));
+    }
+
+    #[test]
+    fn check_features_test() {
+        let (_, mut spell_lang) = parse_neadic("SFX A Y 1\nSFX A 0 a/B .\nSFX B Y 1\nSFX B 0 b .\nNEA DIC {\n    x/A\n    cat\n}\n");
+        let mut word_cache = WordCache::new(10);
+        assert!(word_cache.check_token(&spell_lang, "xab"));
+        assert_eq!(Spell::suggest(&spell_lang, "cta"), vec!["cat"]);
+        spell_lang.set_features(
+            spell_lang.slg_features & !(CheckFeature::AffixContinuation as u32 | CheckFeature::Suggest as u32),
+        );
+        assert!(!word_cache.check_token(&spell_lang, "xab"));
+        assert!(!spell_lang.has_feature(CheckFeature::Suggest));
+        assert!(spell_lang.has_feature(CheckFeature::Compound));
+        assert!(!Spell::check_token(&spell_lang, "xab"));
+        assert!(Spell::check_token(&spell_lang, "xa"));
+        assert!(Spell::suggest(&spell_lang, "cta").is_empty());
+    }
+
+    #[test]
+    fn three_suffixes_test() {
+        let affixes = "SFX A Y 1\nSFX A 0 a/B .\nSFX B Y 1\nSFX B 0 b/C .\nSFX C Y 1\nSFX C 0 c .\n";
+        let dic = "NEA DIC {\n    x/A\n}\n";
+        let (_, spell_lang) = parse_neadic(&format!("{affixes}{dic}"));
+        assert!(Spell::check_token(&spell_lang, "xab"));
+        assert!(!Spell::check_token(&spell_lang, "xabc"));
+        let (_, spell_lang) = parse_neadic(&format!("SUFFIXMAX 3\n{affixes}{dic}"));
+        assert!(Spell::check_token(&spell_lang, "xabc"));
+        assert!(!Spell::check_token(&spell_lang, "xacb"));
+        assert!(!Spell::check_token(&spell_lang, "xbc"));
+    }
+
+    #[test]
+    fn affix_cross_product_test() {
+        let (text_parser, _) = parse_neadic("SFX A Yes 1\nSFX A 0 s .\nSFX B N 1\nSFX B 0 x .\n");
+        let notes: Vec<&String> =
+            text_parser.tps_line_notes.iter().filter(|note| note.contains("Cross product")).collect();
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].contains(":1:"));
+    }
+
+    #[test]
+    fn dictionary_count_memory_test() {
+        let dic = "1000\nword\n";
+        let mut text_parser = TextParser::new();
+        text_parser.tps_showing_details = true;
+        // the reservation fails the same way without depending on the memory of the host
+        text_parser.tps_reserve_max = 999;
+        let mut spell_lang = SpellLang::new("test");
+        text_parser.parse_dictionary_text(&mut spell_lang, &mut StrLineReader::new(TextParser::EXT_DIC, dic));
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("loading stopped")));
+        assert!(!spell_lang.slg_dic_hash.contains_key("word"));
+        text_parser.tps_continue_loading = true;
+        let mut spell_lang = SpellLang::new("test");
+        text_parser.parse_dictionary_text(&mut spell_lang, &mut StrLineReader::new(TextParser::EXT_DIC, dic));
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("without reservation")));
+        assert!(spell_lang.slg_dic_hash.contains_key("word"));
+    }
+
+    #[test]
+    fn dictionary_count_artifacts_test() {
+        let dic = "SET UTF-8\n\u{feff}3 # entries \nword\n";
+        let mut text_parser = TextParser::new();
+        text_parser.tps_showing_details = true;
+        let mut spell_lang = SpellLang::new("test");
+        text_parser.parse_dictionary_text(&mut spell_lang, &mut StrLineReader::new(TextParser::EXT_DIC, dic));
+        assert_eq!(spell_lang.slg_dic_count, 3);
+        assert!(!text_parser.tps_line_notes.iter().any(|note| note.contains("Unexpected") || note.contains("not recognized")));
+        assert!(spell_lang.slg_dic_hash.contains_key("word"));
+        let mut spell_lang = SpellLang::new("test");
+        text_parser.parse_dictionary_text(&mut spell_lang, &mut StrLineReader::new(TextParser::EXT_DIC, "3 entries\n"));
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("Unexpected argument")));
+    }
+
+    #[test]
+    fn validate_affixes_test() {
+        let (_, spell_lang) = parse_neadic("SFX G Y 2\nSFX G e ing [^e]\nSFX G y ies [^aeiou]y\n");
+        let notes = Parser::validate_affixes(&spell_lang);
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].contains("SFX G: strip e, add ing, condition [^e]"));
+        let (_, spell_lang) = parse_neadic("SFX S Y 3\nSFX S 0 s [^y]\nSFX S y ies y\nSFX S 0 s [^y]\n");
+        let notes = Parser::validate_affixes(&spell_lang);
+        assert_eq!(notes, vec!["Duplicate entry in SFX S: strip 0, add s, condition [^y]"]);
+    }
+
+    #[test]
+    fn has_word_with_flag_test() {
+        let (_, spell_lang) = parse_neadic(
+            "FLAG long\nAF 1\nAF G1\nNEA DIC {\n    walk/G1Xy\n    Talk/1\n    run\n}\n",
+        );
+        assert!(spell_lang.has_word_with_flag("walk", "G1"));
+        assert!(spell_lang.has_word_with_flag("walk", "Xy"));
+        assert!(!spell_lang.has_word_with_flag("walk", "Zz"));
+        assert!(spell_lang.has_word_with_flag("Talk", "G1"));
+        assert!(!spell_lang.has_word_with_flag("run", "G1"));
+        assert!(!spell_lang.has_word_with_flag("jump", "G1"));
+    }
+
+    #[test]
+    fn no_words_loaded_test() {
+        let mut text_parser = TextParser::new();
+        let mut spell_lang = SpellLang::new("test");
+        let dic = "2\n# first comment\n# second comment\n";
+        text_parser.parse_dictionary_text(&mut spell_lang, &mut StrLineReader::new(TextParser::EXT_DIC, dic));
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("No words loaded")));
+        let mut text_parser = TextParser::new();
+        let mut spell_lang = SpellLang::new("test");
+        let dic = "1\nword\n";
+        text_parser.parse_dictionary_text(&mut spell_lang, &mut StrLineReader::new(TextParser::EXT_DIC, dic));
+        assert!(!text_parser.tps_line_notes.iter().any(|note| note.contains("No words loaded")));
+        // the supplement dic without words, after the words of the main dic
+        let supplement = "1\n# only a comment\n";
+        text_parser.parse_supplement_dic(&mut spell_lang, &mut StrLineReader::new(TextParser::EXT_DIC, supplement));
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("No words loaded")));
+        let (text_parser, _) = parse_neadic("NEA DIC {\n}\nNEA TESTGOODWORDS {\n}\n");
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("test.neadic:2: No words loaded")));
+        let (text_parser, _) = parse_neadic("NEA DIC {\n    word\n}\n");
+        assert!(!text_parser.tps_line_notes.iter().any(|note| note.contains("No words loaded")));
+    }
+
+    #[test]
+    fn affix_classes_test() {
+        let (_, spell_lang) =
+            parse_neadic("PFX U Y 1\nPFX U 0 un .\nSFX S N 2\nSFX S y ies/U [^aeiou]y\nSFX S 0 s [^y]\n");
+        let classes: Vec<_> = spell_lang.affix_classes().collect();
+        assert_eq!(classes.len(), 2);
+        assert_eq!(classes[0].name(), "U");
+        assert!(classes[0].is_prefix());
+        assert!(classes[0].cross_product());
+        assert_eq!(classes[1].name(), "S");
+        assert!(!classes[1].is_prefix());
+        assert!(!classes[1].cross_product());
+        let entries: Vec<(&str, &str, &str, &[String])> = classes[1]
+            .entries()
+            .map(|entry| (entry.sub(), entry.add(), entry.condition(), entry.next_flags()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("y", "ies", "[^aeiou]y", &[String::from("U")][..]),
+                ("", "s", "[^y]", &[][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn affix_special_flags_test() {
+        let (_, spell_lang) = parse_neadic(
+            "NOSUGGEST !\nWARN W\nSFX A Y 2\nSFX A 0 s/! .\nSFX A 0 ed/W .\nNEA DIC {\n    walk/A\n    talk/AW\n}\n",
+        );
+        assert!(Spell::check_token(&spell_lang, "walks"));
+        assert!(Spell::token_has_special_flag(&spell_lang, "walks", FlagType::FlagNoSuggest));
+        assert!(!Spell::token_has_special_flag(&spell_lang, "walk", FlagType::FlagNoSuggest));
+        assert!(!Spell::token_has_special_flag(&spell_lang, "walked", FlagType::FlagNoSuggest));
+        assert!(Spell::token_has_special_flag(&spell_lang, "walked", FlagType::FlagWarn));
+        assert!(!Spell::token_has_special_flag(&spell_lang, "walks", FlagType::FlagWarn));
+        assert!(Spell::token_has_special_flag(&spell_lang, "talks", FlagType::FlagWarn));
+        assert!(!Spell::token_has_special_flag(&spell_lang, "jumps", FlagType::FlagNoSuggest));
+    }
+
+    #[test]
+    fn normalize_quotes_test() {
+        let (_, mut spell_lang) = parse_neadic("WORDCHARS '\nNEA DIC {\n    don't\n    I\n    know\n}\n");
+        let text = "I don\u{2019}t know";
+        assert!(!Spell::check_text_spans(&spell_lang, text).contains(&(2..9, TokenType::IsGoodWord)));
+        spell_lang.slg_mode_flags |= ModeFlag::NormalizeQuotes as u32;
+        let spans = Spell::check_text_spans(&spell_lang, text);
+        assert!(spans.contains(&(2..9, TokenType::IsGoodWord)));
+        assert!(!spans.iter().any(|span| span.1 == TokenType::IsBadWord));
+    }
+
+    #[test]
+    fn diagnose_test() {
+        let (_, spell_lang) = parse_neadic(
+            "FORBIDDENWORD !\nNEEDAFFIX N\nSFX A Y 1\nSFX A 0 s .\nNEA DIC {\n    UNICEF\n    walk/A\n    walks/!\n    kilo/N\n}\n",
+        );
+        assert!(Spell::diagnose(&spell_lang, "walk").is_accepted());
+        assert_eq!(
+            Spell::diagnose(&spell_lang, "Unicef").rji_reasons,
+            vec![RejectionReason::CaseMismatch(CharCase::Upper)]
+        );
+        assert_eq!(Spell::diagnose(&spell_lang, "walks").rji_reasons, vec![RejectionReason::Forbidden]);
+        assert_eq!(Spell::diagnose(&spell_lang, "kilo").rji_reasons, vec![RejectionReason::NeedsAffix]);
+        assert_eq!(Spell::diagnose(&spell_lang, "jumps").rji_reasons, vec![RejectionReason::NotInDictionary]);
+    }
+
+    #[test]
+    fn suggest_one_edit_test() {
+        let (_, mut spell_lang) = parse_neadic("NOSUGGEST !\nNEA DIC {\n    sat\n    ats\n    as\n    st/!\n}\n");
+        let mut suggestions = Spell::suggest(&spell_lang, "ast");
+        suggestions.sort();
+        assert_eq!(suggestions, vec!["as", "ats", "sat"]);
+        spell_lang.slg_suggest_max = 2;
+        assert_eq!(Spell::suggest(&spell_lang, "ast").len(), 2);
+        let (_, spell_lang) = parse_neadic("TRY sxn\nNEA DIC {\n    test\n    tent\n    text\n}\n");
+        let mut suggestions = Spell::suggest(&spell_lang, "tezt");
+        suggestions.sort();
+        assert_eq!(suggestions, vec!["tent", "test", "text"]);
+    }
+
+    #[test]
+    fn check_word_test() {
+        let (_, spell_lang) =
+            parse_neadic("TRY sxnb\nNOSUGGEST !\nSFX A Y 1\nSFX A 0 s .\nNEA DIC {\n    test/A\n    tent\n    text\n    best/!\n}\n");
+        let result = Spell::check_word(&spell_lang, "tests");
+        assert!(result.wrs_is_correct);
+        assert!(result.wrs_suggestions.is_empty());
+        let result = Spell::check_word(&spell_lang, "tset");
+        assert!(!result.wrs_is_correct);
+        assert_eq!(result.wrs_suggestions, vec!["test"]);
+        let result = Spell::check_word(&spell_lang, "tezt");
+        assert_eq!(result.wrs_suggestions, vec!["text", "test", "tent"]);
+    }
+
+    #[test]
+    fn completions_test() {
+        let (_, spell_lang) =
+            parse_neadic("NEA DIC {\n    London\n    long\n    lone\n    loan\n    walk\n    bad word\n}\n");
+        assert_eq!(spell_lang.completions("lon", 10), vec!["London", "lone", "long"]);
+        assert_eq!(spell_lang.completions("Lon", 2), vec!["London", "lone"]);
+        assert_eq!(spell_lang.completions("", 2), vec!["loan", "London"]);
+        assert!(spell_lang.completions("x", 10).is_empty());
+    }
+
+    #[test]
+    fn ignore_flags_test() {
+        let parse_ignoring = |ignore_flags: Vec<String>| {
+            let mut text_parser = TextParser::new();
+            text_parser.tps_showing_details = true;
+            text_parser.tps_warn.insert(TextParser::SHOW_DIC_OTHER);
+            text_parser.tps_ignore_flags = ignore_flags;
+            let mut spell_lang = SpellLang::new("test");
+            let mut line_reader = StrLineReader::new(
+                TextParser::EXT_NEADIC,
+                "SFX A Y 1\nSFX A 0 s .\nNEA DIC {\n    walk/AX\n    talk/X\n}\n",
+            );
+            text_parser.parse_dictionary_text(&mut spell_lang, &mut line_reader);
+            let unknown_noted = text_parser.tps_line_notes.iter().any(|note| note.contains("Unknown flag"));
+            (unknown_noted, spell_lang)
+        };
+        let (unknown_noted, spell_lang) = parse_ignoring(vec![]);
+        assert!(unknown_noted);
+        assert_eq!(spell_lang.slg_noparse_flags.get("X"), Some(&2));
+        let (unknown_noted, spell_lang) = parse_ignoring(vec![String::from("X")]);
+        assert!(!unknown_noted);
+        assert!(spell_lang.slg_noparse_flags.is_empty());
+        assert!(Spell::check_token(&spell_lang, "walks"));
+        assert!(Spell::check_token(&spell_lang, "talk"));
+    }
+
+    #[test]
+    fn compound_linkers_test() {
+        let (_, spell_lang) =
+            parse_neadic("NEA COMPOUNDLINK s es\nNEA DIC {\n    Arbeit\n    Tag\n}\n");
+        assert_eq!(spell_lang.slg_comp_linkers, vec!["s", "es"]);
+        let remainders: Vec<&str> = spell_lang.strip_compound_linkers("stag").collect();
+        assert_eq!(remainders, vec!["tag"]);
+        let remainders: Vec<&str> = spell_lang.strip_compound_linkers("estag").collect();
+        assert_eq!(remainders, vec!["tag"]);
+        assert_eq!(spell_lang.strip_compound_linkers("s").count(), 0);
+        let (text_parser, _) = parse_neadic("NEA COMPOUNDLINK\n");
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("Expected linking elements")));
+    }
+
+    #[test]
+    fn compound_flag_test() {
+        let (_, spell_lang) = parse_neadic(
+            "COMPOUNDFLAG Y\nCOMPOUNDWORDMAX 3\nNEA COMPOUNDLINK s\nSFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    Schreib/Y\n    Tisch/Y\n    Lampe/YS\n    Arbeit/Y\n    Tag/Y\n    Haus\n}\n",
+        );
+        assert!(Spell::check_token(&spell_lang, "Schreibtischlampe"));
+        assert!(Spell::check_token(&spell_lang, "Tischlampes"));
+        assert!(Spell::check_token(&spell_lang, "SCHREIBTISCH"));
+        assert!(Spell::check_token(&spell_lang, "Arbeitstag"));
+        assert!(!Spell::check_token(&spell_lang, "Tischhaus"));
+        assert!(!Spell::check_token(&spell_lang, "Tischtischtischtisch"));
+    }
+
+    #[test]
+    fn compound_long_word_test() {
+        // each split of the rest is searched once, the exhaustive search wouldn't end in hours
+        for aff in ["COMPOUNDFLAG Y\n", "COMPOUNDFLAG Y\nCOMPOUNDWORDMAX 30\n", "COMPOUNDRULE 1\nCOMPOUNDRULE Y*\n"] {
+            let (_, spell_lang) = parse_neadic(&format!("{aff}NEA DIC {{\n    aaa/Y\n    aaaa/Y\n    aaaaa/Y\n}}\n"));
+            assert!(Spell::check_token(&spell_lang, &"a".repeat(80)));
+            assert!(!Spell::check_token(&spell_lang, &("a".repeat(80) + "b")));
+        }
+    }
+
+    #[test]
+    fn compound_position_test() {
+        let (_, spell_lang) = parse_neadic(
+            "COMPOUNDBEGIN B\nCOMPOUNDMIDDLE M\nCOMPOUNDEND E\nNEA DIC {\n    sun/B\n    flower/ME\n    seed/E\n    oil/B\n}\n",
+        );
+        assert!(Spell::check_token(&spell_lang, "sunflower"));
+        assert!(Spell::check_token(&spell_lang, "sunflowerseed"));
+        assert!(!Spell::check_token(&spell_lang, "flowersun"));
+        assert!(!Spell::check_token(&spell_lang, "sunoilseed"));
+        assert!(!Spell::check_token(&spell_lang, "sunseedflower"));
+    }
+
+    #[test]
+    fn compound_dup_test() {
+        let dic = "COMPOUNDFLAG Y\nNEA DIC {\n    book/Y\n    case/Y\n}\n";
+        let (_, spell_lang) = parse_neadic(dic);
+        assert!(Spell::check_token(&spell_lang, "bookbook"));
+        let (_, spell_lang) = parse_neadic(&format!("CHECKCOMPOUNDDUP\n{dic}"));
+        assert!(Spell::check_token(&spell_lang, "bookcase"));
+        assert!(Spell::check_token(&spell_lang, "bookcasebook"));
+        assert!(!Spell::check_token(&spell_lang, "bookbook"));
+        assert!(!Spell::check_token(&spell_lang, "Bookbook"));
+        assert!(!Spell::check_token(&spell_lang, "casebookbook"));
+    }
+
+    #[test]
+    fn only_in_compound_position_test() {
+        let (_, spell_lang) = parse_neadic(
+            "ONLYINCOMPOUND O\nCOMPOUNDBEGIN B\nCOMPOUNDMIDDLE M\nCOMPOUNDEND E\nNEA DIC {\n    foot/BO\n    ball/BME\n    game/E\n}\n",
+        );
+        assert!(!Spell::check_token(&spell_lang, "foot"));
+        assert!(Spell::check_token(&spell_lang, "football"));
+        assert!(Spell::check_token(&spell_lang, "footballgame"));
+        assert!(!Spell::check_token(&spell_lang, "ballfoot"));
+        assert!(!Spell::check_token(&spell_lang, "ballfootgame"));
+    }
+
+    #[test]
+    fn compound_rep_test() {
+        let dic = "COMPOUNDFLAG Y\nREP 1\nREP tt t\nNEA DIC {\n    cat/Y\n    tail/Y\n    catail\n}\n";
+        let (_, spell_lang) = parse_neadic(dic);
+        assert!(Spell::check_token(&spell_lang, "cattail"));
+        let (_, spell_lang) = parse_neadic(&format!("CHECKCOMPOUNDREP\n{dic}"));
+        assert!(!Spell::check_token(&spell_lang, "cattail"));
+        assert!(!Spell::check_token(&spell_lang, "Cattail"));
+        assert!(Spell::check_token(&spell_lang, "tailcat"));
+    }
+
+    #[test]
+    fn compound_case_test() {
+        let dic = "COMPOUNDFLAG Y\nNEA DIC {\n    foo/Y\n    bar/Y\n}\n";
+        let (_, spell_lang) = parse_neadic(dic);
+        assert!(Spell::check_token(&spell_lang, "fooBar"));
+        let (_, spell_lang) = parse_neadic(&format!("CHECKCOMPOUNDCASE\n{dic}"));
+        assert!(!Spell::check_token(&spell_lang, "fooBar"));
+        assert!(!Spell::check_token(&spell_lang, "FooBar"));
+        assert!(Spell::check_token(&spell_lang, "foobar"));
+        assert!(Spell::check_token(&spell_lang, "Foobar"));
+        assert!(Spell::check_token(&spell_lang, "FOOBAR"));
+    }
+
+    #[test]
+    fn compound_permit_forbid_test() {
+        let (_, spell_lang) = parse_neadic(
+            "COMPOUNDFLAG Y\nCOMPOUNDPERMITFLAG P\nCOMPOUNDFORBIDFLAG F\nPFX U Y 1\nPFX U 0 un .\nSFX S Y 1\nSFX S 0 s .\nSFX E Y 1\nSFX E 0 er/P .\nSFX G Y 1\nSFX G 0 ing/F .\nNEA DIC {\n    foot/YUSEG\n    ball/YUSEG\n}\n",
+        );
+        // the affixes at the edges of the compound
+        assert!(Spell::check_token(&spell_lang, "footballs"));
+        assert!(Spell::check_token(&spell_lang, "unfootball"));
+        // the affixes inside the compound
+        assert!(!Spell::check_token(&spell_lang, "footsball"));
+        assert!(!Spell::check_token(&spell_lang, "footunball"));
+        assert!(Spell::check_token(&spell_lang, "footerball"));
+        // the forbidden affix
+        assert!(Spell::check_token(&spell_lang, "balling"));
+        assert!(!Spell::check_token(&spell_lang, "footballing"));
+    }
+
+    #[test]
+    fn force_ucase_test() {
+        let (_, spell_lang) = parse_neadic(
+            "COMPOUNDFLAG Y\nFORCEUCASE U\nNEA DIC {\n    foo/Y\n    bar/YU\n    baz/Y\n}\n",
+        );
+        assert!(Spell::check_token(&spell_lang, "Foobar"));
+        assert!(Spell::check_token(&spell_lang, "FOOBAR"));
+        assert!(!Spell::check_token(&spell_lang, "foobar"));
+        assert!(Spell::check_token(&spell_lang, "barfoo"));
+        assert!(Spell::check_token(&spell_lang, "foobaz"));
+    }
+
+    #[test]
+    fn circumfix_test() {
+        let (_, spell_lang) = parse_neadic(
+            "CIRCUMFIX X\nPFX A Y 1\nPFX A 0 leg/X .\nSFX B Y 2\nSFX B 0 obb .\nSFX B 0 obb/AX .\nNEA DIC {\n    nagy/AB\n}\n",
+        );
+        assert!(Spell::check_token(&spell_lang, "nagy"));
+        assert!(Spell::check_token(&spell_lang, "nagyobb"));
+        assert!(Spell::check_token(&spell_lang, "legnagyobb"));
+        assert!(!Spell::check_token(&spell_lang, "legnagy"));
+    }
+
+    #[test]
+    fn suggest_test() {
+        let (_, mut spell_lang) =
+            parse_neadic("NEA DIC {\n    form\n    from\n    for\n    fork\n    fro\n    fir\n}\n");
+        // one edit away, then two transpositions or deletions away
+        assert_eq!(Spell::suggest(&spell_lang, "fomr"), vec!["form", "for", "from", "fro"]);
+        spell_lang.slg_suggest_max = 2;
+        assert_eq!(Spell::suggest(&spell_lang, "fomr"), vec!["form", "for"]);
+    }
+
+    #[test]
+    fn try_order_test() {
+        let dic = "NEA DIC {\n    cat\n    cas\n    cab\n}\n";
+        let (_, spell_lang) = parse_neadic(&format!("TRY ts\n{dic}"));
+        assert_eq!(spell_lang.try_chars(), "ts");
+        assert_eq!(Spell::suggest(&spell_lang, "ca"), vec!["cat", "cas"]);
+        let (_, spell_lang) = parse_neadic(&format!("TRY st\n{dic}"));
+        assert_eq!(Spell::suggest(&spell_lang, "ca"), vec!["cas", "cat"]);
+        // without TRY, the characters of the dictionary, the most frequent first
+        let (_, spell_lang) = parse_neadic(dic);
+        assert_eq!(spell_lang.try_chars(), "");
+        assert_eq!(spell_lang.suggest_alphabet(), vec!['a', 'c', 'b', 's', 't']);
+        assert_eq!(Spell::suggest(&spell_lang, "ca"), vec!["cab", "cas", "cat"]);
+        // only the most frequent characters of a large alphabet
+        let mut spell_lang = SpellLang::new("zh");
+        let word: String = ('\u{4e00}'..'\u{4e80}').collect();
+        spell_lang.add_word(&word);
+        spell_lang.add_word("\u{4e01}");
+        spell_lang.index_words();
+        assert_eq!(spell_lang.suggest_alphabet().len(), SpellLang::WORD_CHARS_MAX);
+        assert_eq!(spell_lang.suggest_alphabet()[0], '\u{4e01}');
+    }
+
+    #[test]
+    fn rep_suggestions_test() {
+        let dic = "NEA DIC {\n    fife\n    a\n    lot\n    shot\n}\n";
+        let (_, spell_lang) = parse_neadic(dic);
+        assert!(Spell::suggest(&spell_lang, "phiphe").is_empty());
+        assert_eq!(Spell::suggest(&spell_lang, "alot"), vec!["lot", "a lot"]);
+        let (_, spell_lang) = parse_neadic(&format!("REP 3\nREP ph f\nREP alot a_lot\nREP ^sj sh\n{dic}"));
+        assert_eq!(Spell::suggest(&spell_lang, "phiphe"), vec!["fife"]);
+        // ranked above the generic edits
+        assert_eq!(Spell::suggest(&spell_lang, "alot"), vec!["a lot", "lot"]);
+        assert_eq!(Spell::suggest(&spell_lang, "sjot"), vec!["shot"]);
+    }
+
+    #[test]
+    fn map_suggestions_test() {
+        let dic = "NEA DIC {\n    café\n    straße\n}\n";
+        let (_, spell_lang) = parse_neadic(&format!("MAP 2\nMAP eéè\nMAP (ss)(ß)\n{dic}"));
+        assert_eq!(spell_lang.slg_map_groups, vec![vec!["e", "é", "è"], vec!["ss", "ß"]]);
+        assert_eq!(Spell::suggest(&spell_lang, "cafe"), vec!["café"]);
+        assert_eq!(Spell::suggest(&spell_lang, "strasse"), vec!["straße"]);
+        let (_, spell_lang) = parse_neadic(&format!("MAXNGRAMSUGS 0\n{dic}"));
+        assert!(Spell::suggest(&spell_lang, "strasse").is_empty());
+    }
+
+    #[test]
+    fn phonetic_suggestions_test() {
+        let dic = "NEA DIC {\n    knife\n    city\n    phone\n}\n";
+        let (_, spell_lang) = parse_neadic(&format!("MAXNGRAMSUGS 0\n{dic}"));
+        assert!(Spell::suggest(&spell_lang, "nyfe").is_empty());
+        let phone = "PHONE 6\nPHONE PH F\nPHONE CK K\nPHONE C(EIY)- S\nPHONE Y I\nPHONE E$ _\nPHONE ^KN N\n";
+        let (_, spell_lang) = parse_neadic(&format!("{phone}{dic}"));
+        let phone_rule = Parser::parse_phone_rule("C(EIY)-", "S");
+        assert_eq!(phone_rule.phr_chars, vec!['C']);
+        assert_eq!(phone_rule.phr_group, vec!['E', 'I', 'Y']);
+        assert_eq!(phone_rule.phr_ahead, 1);
+        assert_eq!(Spell::phonetic_code(&spell_lang, "knife"), "NIF");
+        assert_eq!(Spell::phonetic_code(&spell_lang, "city"), "SITI");
+        assert_eq!(Spell::phonetic_code(&spell_lang, "phone"), "FON");
+        assert_eq!(Spell::phonetic_code(&spell_lang, "acknowledge"), "AKNOWLEDG");
+        // not found by the edits
+        assert_eq!(Spell::suggest(&spell_lang, "nyfe"), vec!["knife"]);
+        assert_eq!(Spell::suggest(&spell_lang, "Nyfe"), vec!["Knife"]);
+        // a near phonetic code
+        assert_eq!(Spell::suggest(&spell_lang, "fohne"), vec!["phone"]);
+        // the priorities of the overlapping rules, and a replacement put back into the word
+        let phone_rules = |rules: &[(&str, &str)]| -> Vec<PhoneRule> {
+            rules.iter().map(|(pattern, replacement)| Parser::parse_phone_rule(pattern, replacement)).collect()
+        };
+        assert_eq!(Spell::phonetic_code_by_rules(&phone_rules(&[("AB", "X"), ("BC", "Y")]), "abc"), "AY");
+        assert_eq!(Spell::phonetic_code_by_rules(&phone_rules(&[("AB", "X"), ("BC1", "Y")]), "abc"), "XC");
+        assert_eq!(Spell::phonetic_code_by_rules(&phone_rules(&[("AB", "X"), ("BC", "Y")]), "ab"), "X");
+        assert_eq!(Spell::phonetic_code_by_rules(&phone_rules(&[("PH", "F"), ("FF", "V")]), "phf"), "FF");
+        assert_eq!(Spell::phonetic_code_by_rules(&phone_rules(&[("PH<", "F"), ("FF", "V")]), "phf"), "V");
+    }
+
+    #[test]
+    fn ngram_suggestions_test() {
+        let dic = "NEA DIC {\n    necessary\n    accessory\n    cat\n    dog\n}\n";
+        let (_, spell_lang) = parse_neadic(dic);
+        // not found by the edits
+        assert_eq!(Spell::suggest(&spell_lang, "nesesarry"), vec!["necessary"]);
+        assert_eq!(Spell::suggest(&spell_lang, "Nesesarry"), vec!["Necessary"]);
+        // the most similar word below the threshold, only without ONLYMAXDIFF
+        assert_eq!(Spell::suggest(&spell_lang, "gdoqz"), vec!["dog"]);
+        let (_, spell_lang) = parse_neadic(&format!("ONLYMAXDIFF\n{dic}"));
+        assert!(Spell::suggest(&spell_lang, "gdoqz").is_empty());
+        assert_eq!(Spell::suggest(&spell_lang, "nesesarry"), vec!["necessary"]);
+        let (_, spell_lang) = parse_neadic(&format!("MAXDIFF 10\nMAXNGRAMSUGS 2\n{dic}"));
+        assert_eq!(Spell::suggest(&spell_lang, "nesesarry"), vec!["necessary", "accessory"]);
+        let (_, spell_lang) = parse_neadic(&format!("MAXNGRAMSUGS 0\n{dic}"));
+        assert!(Spell::suggest(&spell_lang, "nesesarry").is_empty());
+        // the words added after loading are indexed too
+        let (_, mut spell_lang) = parse_neadic(dic);
+        spell_lang.add_word("neighbourhood");
+        assert_eq!(Spell::suggest(&spell_lang, "nieghborhud"), vec!["neighbourhood"]);
+    }
+
+    #[test]
+    fn split_suggestions_test() {
+        let dic = "NEA DIC {\n    the\n    cat\n    a\n}\n";
+        let (_, spell_lang) = parse_neadic(dic);
+        assert_eq!(Spell::suggest(&spell_lang, "thecat"), vec!["the cat"]);
+        assert_eq!(Spell::suggest(&spell_lang, "acat"), vec!["cat", "a cat"]);
+        let (_, spell_lang) = parse_neadic(&format!("NOSPLITSUGS\nMAXNGRAMSUGS 0\n{dic}"));
+        assert!(Spell::suggest(&spell_lang, "thecat").is_empty());
+    }
+
+    #[test]
+    fn check_text_issues_test() {
+        let (_, spell_lang) = parse_neadic("NEA DIC {\n    the\n    cat\n    sat\n}\n");
+        let text = "The the cat sta. Cat, cat";
+        assert_eq!(Spell::repeated_word_spans(&spell_lang, text), vec![4..7]);
+        let issues = Spell::check_text_issues(&spell_lang, text);
+        let expected = vec![
+            Issue { isu_kind: IssueKind::RepeatedWord, isu_span: 4..7, isu_suggestions: vec![] },
+            Issue { isu_kind: IssueKind::Misspelling, isu_span: 12..15, isu_suggestions: vec!["sat".to_string()] },
+        ];
+        assert_eq!(issues, expected);
+    }
+
+    #[test]
+    fn table_suggestions_test() {
+        let dic = "NEA DIC {\n    phone\n    quick\n}\n";
+        let (_, spell_lang) = parse_neadic(&format!("MAXNGRAMSUGS 0\n{dic}"));
+        assert!(Spell::check_word(&spell_lang, "fone").wrs_suggestions.is_empty());
+        assert!(Spell::check_word(&spell_lang, "kwick").wrs_suggestions.is_empty());
+        let (_, spell_lang) = parse_neadic(&format!("PHONE 1\nPHONE PH F\nREP 1\nREP kw qu\n{dic}"));
+        assert_eq!(Spell::check_word(&spell_lang, "fone").wrs_suggestions, vec!["phone"]);
+        assert_eq!(Spell::check_word(&spell_lang, "kwick").wrs_suggestions, vec!["quick"]);
+    }
+
+    #[test]
+    fn soft_break_test() {
+        let (_, mut spell_lang) = parse_neadic(
+            "WORDCHARS -0123456789\nNEA SOFTPREFIX USB-\nNEA SOFTSUFFIX GB\nNEA DIC {\n    enabled\n}\n",
+        );
+        let checked = Spell::check_text(&spell_lang, "USB-enabled 2GB USB-");
+        let expected = [
+            ("USB-", TokenType::NotWord),
+            ("enabled", TokenType::IsGoodWord),
+            (" ", TokenType::NotWord),
+            ("2", TokenType::NotWord),
+            ("GB", TokenType::NotWord),
+            (" ", TokenType::NotWord),
+            ("USB-", TokenType::IsBadWord),
+        ];
+        assert_eq!(checked, expected.map(|(token, token_type)| (token.to_string(), token_type)));
+        spell_lang.set_features(CheckFeature::ALL & !(CheckFeature::SoftSplit as u32));
+        let checked = Spell::check_text(&spell_lang, "USB-enabled");
+        assert_eq!(checked, vec![("USB-enabled".to_string(), TokenType::IsBadWord)]);
+        let (text_parser, _) = parse_neadic("NEA SOFTPREFIX\n");
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("Expected soft breaks after SOFTPREFIX")));
+    }
+
+    #[test]
+    fn affix_usage_test() {
+        let (_, spell_lang) = parse_neadic(
+            "PFX U Y 1\nPFX U 0 un .\nSFX S Y 1\nSFX S 0 s .\nSFX D Y 1\nSFX D 0 ed .\nNEA DIC {\n    lock/USD\n}\n",
+        );
+        let mut affix_usage = AffixUsage::new(&spell_lang);
+        assert_eq!(affix_usage.unused_classes(&spell_lang), vec!["U", "S", "D"]);
+        for word in ["locks", "lock", "locksmith"] {
+            affix_usage.check_token(&spell_lang, word);
+        }
+        assert_eq!(affix_usage.unused_classes(&spell_lang), vec!["U", "D"]);
+        assert!(affix_usage.check_token(&spell_lang, "unlocked"));
+        assert!(affix_usage.unused_classes(&spell_lang).is_empty());
+    }
+
+    #[test]
+    fn ignore_suggestions_test() {
+        let (_, spell_lang) = parse_neadic(
+            "IGNORE \u{ad}\nICONV 1\nICONV \u{2bc} '\nOCONV 1\nOCONV ' \u{2bc}\nNEA DIC {\n    word\n    don't\n}\n",
+        );
+        // one suggestion, not also "wo\u{ad}rd" with the soft hyphen
+        assert_eq!(Spell::check_word(&spell_lang, "wo\u{ad}rdd").wrs_suggestions, vec!["word"]);
+        assert_eq!(Spell::check_word(&spell_lang, "do\u{2bc}nt").wrs_suggestions, vec!["don\u{2bc}t"]);
+    }
+
+    #[test]
+    fn word_len_max_test() {
+        let (_, mut spell_lang) = parse_neadic("SFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    cat/S\n}\n");
+        let long_word = "cats".repeat(2500);
+        assert_eq!(spell_lang.slg_word_len_max, 100);
+        assert!(!spell_lang.fits_word_len_max(&long_word));
+        assert!(spell_lang.fits_word_len_max(&"cats".repeat(25)));
+        // the longer words are rejected alone without suggestions, and skipped in the text
+        assert!(!Spell::check_token(&spell_lang, &long_word));
+        assert!(!Spell::check_token(&spell_lang, &format!("{}cat", "cats".repeat(25))));
+        let word_result = Spell::check_word(&spell_lang, &long_word);
+        assert!(!word_result.wrs_is_correct);
+        assert_eq!(word_result.wrs_suggestions, Vec::<String>::new());
+        let checked = Spell::check_text(&spell_lang, &format!("cats {long_word}"));
+        assert_eq!(checked[0], ("cats".to_string(), TokenType::IsGoodWord));
+        assert_eq!(checked[2], (long_word, TokenType::NotWord));
+        spell_lang.slg_word_len_max = 3;
+        assert!(!Spell::check_token(&spell_lang, "cats"));
+        assert!(Spell::check_token(&spell_lang, "cat"));
+        assert!(!Spell::check_token(&spell_lang, "cst"));
+    }
+
+    #[test]
+    fn skip_pattern_test() {
+        let (mut text_parser, _) = parse_neadic("");
+        text_parser.tps_skip_patterns.push(SkipPattern::new(r"v\d+"));
+        let spell_lang = text_parser.parse_lang_readers(
+            StrLineReader::new(TextParser::EXT_AFF, "WORDCHARS 0123456789\n"),
+            StrLineReader::new(TextParser::EXT_DIC, "1\nuse\n"),
+        );
+        let checked = Spell::check_text(&spell_lang, "use v12 v3b");
+        assert_eq!(checked[0], ("use".to_string(), TokenType::IsGoodWord));
+        assert_eq!(checked[2], ("v12".to_string(), TokenType::NotWord));
+        assert_eq!(checked[4], ("v3b".to_string(), TokenType::IsBadWord));
+        let pattern = SkipPattern::new(r"^[a-f0-9]+-?[^x]*$");
+        assert!(pattern.skp_error.is_none());
+        assert!(pattern.match_token("c0ffee-ok"));
+        assert!(pattern.match_token("beef"));
+        assert!(!pattern.match_token("beef-box"));
+        assert!(SkipPattern::new(r"x.\w*").match_token("xyz_9"));
+        assert_eq!(SkipPattern::new("(v)").skp_error, Some(("Unexpected character in pattern", 1)));
+        // many ways to split the token, but each position is tried once
+        let pattern = SkipPattern::new(&"a*".repeat(30));
+        assert!(!pattern.match_token(&format!("{}b", "a".repeat(40))));
+        assert!(pattern.match_token(&"a".repeat(40)));
+        assert_eq!(SkipPattern::new("+").skp_error, Some(("Unexpected repetition (*+?) in pattern", 1)));
+    }
+
+    #[test]
+    fn ph_field_test() {
+        let (_, spell_lang) =
+            parse_neadic("NEA DIC {\n    which ph:witch\n    Marseille ph:maarsayl po:noun\n    bad word\n}\n");
+        assert!(Spell::check_token(&spell_lang, "which"));
+        assert!(!Spell::check_token(&spell_lang, "witch"));
+        assert!(Spell::check_token(&spell_lang, "bad word"));
+        assert_eq!(spell_lang.slg_dic_hash["marseille"].den_morph, vec!["ph:maarsayl", "po:noun"]);
+        assert_eq!(Spell::check_word(&spell_lang, "witch").wrs_suggestions, vec!["which"]);
+        assert_eq!(Spell::check_word(&spell_lang, "Witch").wrs_suggestions, vec!["Which"]);
+        assert_eq!(Spell::check_word(&spell_lang, "maarsayl").wrs_suggestions, vec!["Marseille"]);
+    }
+
+    #[test]
+    fn char_class_test() {
+        let (_, spell_lang) =
+            parse_neadic("WORDCHARS 0123456789'\nIGNORE \u{ad}\nNEA DIC {\n    don't\n}\n");
+        assert_eq!(spell_lang.char_class('a'), CharClass::Letter);
+        assert_eq!(spell_lang.char_class('\''), CharClass::WordCharOptional);
+        assert_eq!(spell_lang.char_class('\u{ad}'), CharClass::WordCharOptional);
+        assert_eq!(spell_lang.char_class('5'), CharClass::Digit);
+        assert_eq!(spell_lang.char_class(' '), CharClass::NonWord);
+        let (_, spell_lang) = parse_neadic("NEA DIC {\n    word\n}\n");
+        assert_eq!(spell_lang.char_class('5'), CharClass::NonWord);
+    }
+
+    #[test]
+    fn compound_rule_test() {
+        let (_, spell_lang) = parse_neadic(
+            "COMPOUNDMIN 1\nONLYINCOMPOUND c\nCOMPOUNDRULE 2\nCOMPOUNDRULE n*1t\nCOMPOUNDRULE n*mp\nNEA DIC {\n    1/n1\n    2/nm\n    3/n\n    st/tc\n    nd/pc\n}\n",
+        );
+        assert_eq!(spell_lang.slg_compoundrule_items[0], vec![("n".to_string(), '*'), ("1".to_string(), ' '), ("t".to_string(), ' ')]);
+        assert!(Spell::check_token(&spell_lang, "1st"));
+        assert!(Spell::check_token(&spell_lang, "231st"));
+        assert!(Spell::check_token(&spell_lang, "32nd"));
+        assert!(!Spell::check_token(&spell_lang, "3st"));
+        assert!(!Spell::check_token(&spell_lang, "1nd"));
+        assert!(!Spell::check_token(&spell_lang, "st"));
+        let (_, spell_lang) = parse_neadic(
+            "FLAG long\nCOMPOUNDRULE 1\nCOMPOUNDRULE (aa)(bb)?(cc)\nNEA DIC {\n    foo/aa\n    bar/bb\n    baz/cc\n}\n",
+        );
+        assert!(Spell::check_token(&spell_lang, "foobarbaz"));
+        assert!(Spell::check_token(&spell_lang, "foobaz"));
+        assert!(!Spell::check_token(&spell_lang, "foobarbarbaz"));
+        assert!(!Spell::check_token(&spell_lang, "barbaz"));
+        let (_, spell_lang) = parse_neadic(
+            "FLAG num\nCOMPOUNDRULE 1\nCOMPOUNDRULE (1)(2)*(3)\nNEA DIC {\n    foo/1\n    bar/2\n    baz/3\n}\n",
+        );
+        assert!(Spell::check_token(&spell_lang, "foobarbarbaz"));
+        assert!(!Spell::check_token(&spell_lang, "foobarbar"));
+    }
+
+    #[test]
+    fn word_cache_test() {
+        let (_, mut spell_lang) = parse_neadic("SFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    cat/S\n}\n");
+        let mut word_cache = WordCache::new(2);
+        assert!(word_cache.check_token(&spell_lang, "cats"));
+        assert!(word_cache.check_token(&spell_lang, "cats"));
+        assert!(!word_cache.check_token(&spell_lang, "dog"));
+        assert_eq!(word_cache.len(), 2);
+        spell_lang.add_word("dog");
+        assert!(word_cache.check_token(&spell_lang, "dog"));
+        assert_eq!(word_cache.len(), 1);
+        assert!(!word_cache.check_token(&spell_lang, "dogs"));
+        assert!(word_cache.check_token(&spell_lang, "Cat"));
+        assert_eq!(word_cache.len(), 1);
+        assert_eq!(spell_lang.completions("d", 5), vec!["dog"]);
+        // the entry of a dictionary word keeps its flags
+        spell_lang.add_word("cat");
+        assert!(word_cache.check_token(&spell_lang, "cats"));
+        spell_lang.add_word("Dog");
+        assert_eq!(spell_lang.slg_dic_hash["dog"].den_homonyms.len(), 1);
+        // the same cache with another dictionary
+        let (_, other_lang) = parse_neadic("NEA DIC {\n    cow\n}\n");
+        assert!(!word_cache.check_token(&other_lang, "cats"));
+        assert!(word_cache.check_token(&spell_lang, "cats"));
+    }
+
+    #[test]
+    fn forbidden_word_test() {
+        let (_, spell_lang) = parse_neadic(
+            "FORBIDDENWORD !\nCOMPOUNDFLAG Y\nSFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    sheep/S\n    sheeps/!\n    foot/SY\n    ball/SY\n    football/!\n    bad/S!\n}\n",
+        );
+        assert!(Spell::check_token(&spell_lang, "sheep"));
+        assert!(!Spell::check_token(&spell_lang, "sheeps"));
+        assert!(!Spell::check_token(&spell_lang, "Sheeps"));
+        assert!(Spell::check_token(&spell_lang, "footballs"));
+        assert!(!Spell::check_token(&spell_lang, "football"));
+        assert!(!Spell::check_token(&spell_lang, "bads"));
+        assert_eq!(Spell::diagnose(&spell_lang, "sheeps").rji_reasons, vec![RejectionReason::Forbidden]);
+        assert_eq!(Spell::diagnose(&spell_lang, "football").rji_reasons, vec![RejectionReason::Forbidden]);
+        // accepted both by the suffix and as a compound, unless forbidden
+        let aff = "FORBIDDENWORD !\nCOMPOUNDFLAG Y\nSFX S Y 1\nSFX S 0 s .\n";
+        let dic = "NEA DIC {\n    foot/Y\n    ball/SY\n    football/S\n    Ball/!\n";
+        let (_, spell_lang) = parse_neadic(&format!("{aff}{dic}}}\n"));
+        assert!(Spell::check_token(&spell_lang, "footballs"));
+        assert!(Spell::check_token(&spell_lang, "ball"));
+        assert!(!Spell::check_token(&spell_lang, "Ball"));
+        let (_, spell_lang) = parse_neadic(&format!("{aff}{dic}    footballs/!\n}}\n"));
+        assert!(!Spell::check_token(&spell_lang, "footballs"));
+        assert!(!Spell::check_token(&spell_lang, "FOOTBALLS"));
+    }
+
+    #[test]
+    fn need_affix_test() {
+        let (_, spell_lang) = parse_neadic(
+            "NEEDAFFIX N\nSFX S Y 1\nSFX S 0 s .\nSFX Z Y 1\nSFX Z 0 0 .\nNEA DIC {\n    kilo/NS\n    mega/NSZ\n    giga/S\n}\n",
+        );
+        assert!(!Spell::check_token(&spell_lang, "kilo"));
+        assert!(Spell::check_token(&spell_lang, "kilos"));
+        assert!(Spell::check_token(&spell_lang, "mega"));
+        assert!(Spell::check_token(&spell_lang, "megas"));
+        assert!(Spell::check_token(&spell_lang, "giga"));
+        assert_eq!(Spell::diagnose(&spell_lang, "kilo").rji_reasons, vec![RejectionReason::NeedsAffix]);
+    }
+
+    #[test]
+    fn keyboard_layout_test() {
+        let (_, mut spell_lang) = parse_neadic("NEA DIC {\n    fat\n    fax\n}\n");
+        assert_eq!(spell_lang.keyboard_layout(), "qwertyuiop|asdfghjkl|zxcvbnm");
+        // the neighbouring key first, then the other characters of the dictionary
+        assert_eq!(Spell::check_word(&spell_lang, "faz").wrs_suggestions, vec!["fax", "fat"]);
+        spell_lang.slg_keyboard = String::from("qwertz");
+        assert_eq!(Spell::check_word(&spell_lang, "faz").wrs_suggestions, vec!["fat", "fax"]);
+        let (_, spell_lang) = parse_neadic("KEY azertyuiop|qsdfghjklm|wxcvbn\nNEA DIC {\n    word\n}\n");
+        assert_eq!(spell_lang.keyboard_layout(), "azertyuiop|qsdfghjklm|wxcvbn");
+        assert_eq!(Spell::adjacent_keys(spell_lang.keyboard_layout(), 'q'), vec!['s']);
+        let adjacency = Parser::parse_key_layout("qwe|asd|ws");
+        assert_eq!(adjacency[&'w'], vec!['q', 'e', 's']);
+        assert_eq!(adjacency[&'a'], vec!['s']);
+        // the adjacent key first, before the other substitution by the dictionary characters
+        let dic = "NEA DIC {\n    mat\n    sat\n}\n";
+        let (_, spell_lang) = parse_neadic(&format!("KEY qwertyuiop|asdfghjkl|zxcvbnm\n{dic}"));
+        assert_eq!(Spell::suggest(&spell_lang, "dat"), vec!["sat", "mat"]);
+        let (_, spell_lang) = parse_neadic(&format!("KEY qwertyuiop|asdfghjkl|zxcvbnm|sj\n{dic}"));
+        assert_eq!(Spell::suggest(&spell_lang, "jat"), vec!["sat", "mat"]);
+    }
+
+    #[test]
+    fn continuation_cycles_test() {
+        let (text_parser, spell_lang) = parse_neadic(
+            "SFX A Y 1\nSFX A 0 a/B .\nSFX B Y 1\nSFX B 0 b/A .\nSFX C Y 1\nSFX C 0 c/C .\nNEA DIC {\n    x/AC\n}\n",
+        );
+        let notes = text_parser.tps_line_notes.join("\n");
+        assert!(notes.contains("Continuation flags form a cycle: A -> B -> A"));
+        assert!(notes.contains("Continuation flags form a cycle: C -> C"));
+        assert!(Spell::check_token(&spell_lang, "xc"));
+        assert!(!Spell::check_token(&spell_lang, "xcc"));
+        assert!(!Spell::check_token(&spell_lang, "xabababababababab"));
+    }
+
+    #[test]
+    fn only_in_compound_test() {
+        let (_, spell_lang) = parse_neadic(
+            "COMPOUNDFLAG Y\nONLYINCOMPOUND O\nSFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    foot/SY\n    ball/SYO\n}\n",
+        );
+        assert!(!Spell::check_token(&spell_lang, "ball"));
+        assert!(!Spell::check_token(&spell_lang, "balls"));
+        assert!(Spell::check_token(&spell_lang, "football"));
+        assert!(Spell::check_token(&spell_lang, "footballs"));
+        assert!(Spell::check_token(&spell_lang, "ballfoot"));
+        assert_eq!(Spell::diagnose(&spell_lang, "ball").rji_reasons, vec![RejectionReason::OnlyInCompound]);
+    }
+
+    #[test]
+    fn keep_case_test() {
+        let (_, spell_lang) = parse_neadic(
+            "KEEPCASE K\nSFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    iPod/KS\n    'S-Gravenhage/K\n    's-Gravenhage\n    sony/K\n    NASA/K\n    web\n}\n",
+        );
+        assert!(Spell::check_token(&spell_lang, "iPod"));
+        assert!(Spell::check_token(&spell_lang, "iPods"));
+        assert!(!Spell::check_token(&spell_lang, "IPOD"));
+        assert!(!Spell::check_token(&spell_lang, "ipod"));
+        assert!(Spell::check_token(&spell_lang, "'s-Gravenhage"));
+        assert!(Spell::check_token(&spell_lang, "'S-Gravenhage"));
+        assert!(!Spell::check_token(&spell_lang, "'S-GRAVENHAGE"));
+        assert!(Spell::check_token(&spell_lang, "sony"));
+        assert!(!Spell::check_token(&spell_lang, "Sony"));
+        assert!(!Spell::check_token(&spell_lang, "SONY"));
+        assert!(Spell::check_token(&spell_lang, "NASA"));
+        assert!(!Spell::check_token(&spell_lang, "nasa"));
+        assert!(Spell::check_token(&spell_lang, "WEB"));
+    }
+
+    #[test]
+    fn case_suggestions_test() {
+        let (_, mut spell_lang) = parse_neadic(
+            "KEEPCASE K\nSFX S Y 1\nSFX S 0 's .\nNEA DIC {\n    London/KS\n    condon\n    iPod/K\n    ipad\n}\n",
+        );
+        assert!(!Spell::check_token(&spell_lang, "london"));
+        assert_eq!(Spell::suggest(&spell_lang, "london")[0], "London");
+        assert_eq!(Spell::suggest(&spell_lang, "london's")[0], "London's");
+        assert_eq!(Spell::suggest(&spell_lang, "ipod"), vec!["iPod", "ipad"]);
+        assert_eq!(Spell::suggest(&spell_lang, "IPOD")[0], "iPod");
+        spell_lang.add_word("eBay");
+        assert_eq!(Spell::suggest(&spell_lang, "ebay")[0], "eBay");
+    }
+
+    #[test]
+    fn missing_table_argument_test() {
+        // found by fuzzing, the tags without arguments
+        let (text_parser, _) = parse_neadic("AF\nCOMPOUNDRULE\nAF 1\nAF\nCOMPOUNDRULE 1\nCOMPOUNDRULE\n");
+        let notes = text_parser.tps_line_notes.join("\n");
+        assert!(notes.contains("Missing argument (4 occurrences)"), "{notes}");
+    }
+
+    #[test]
+    fn from_readers_test() {
+        let aff = StrLineReader::new(TextParser::EXT_AFF, "SFX A Y 1\nSFX A 0 s .\n");
+        let dic = StrLineReader::new(TextParser::EXT_DIC, "2\nwalk/A\ntalk\n");
+        let spell_lang = SpellLang::from_readers(aff, dic);
+        assert_eq!(spell_lang.slg_code, "test");
+        assert!(Spell::check_token(&spell_lang, "walks"));
+        assert!(Spell::check_token(&spell_lang, "talk"));
+        assert!(!Spell::check_token(&spell_lang, "talks"));
+    }
+
+    #[test]
+    fn export_aff_dic_test() {
+        let aff = "FLAG long\nTRY esianrtolcdugmphbyfvkwz\nLANG en_GB\nCOMPOUNDMIN 2\nREP 1\nREP f ph\nCOMPOUNDFLAG Cp\n\
+                   NOSUGGEST Ns\nPFX Re Y 1\nPFX Re 0 re .\nSFX Sg Y 2\nSFX Sg 0 s/Ed [^sy]\nSFX Sg y ies y\n\
+                   SFX Ed N 1\nSFX Ed 0 ed .\n";
+        let dic = "6\nwalk/ReSgCp\nfly/Sg\nLondon\nshit/Ns ph:shiit\nmay po:verb\nMay po:noun\n";
+        let spell_lang = SpellLang::from_readers(
+            StrLineReader::new(TextParser::EXT_AFF, aff),
+            StrLineReader::new(TextParser::EXT_DIC, dic),
+        );
+        let exported_aff = spell_lang.export_aff();
+        let exported_dic = spell_lang.export_dic();
+        assert!(exported_aff.contains("\nSFX Sg 0 s/Ed [^sy]\n"), "{exported_aff}");
+        assert!(exported_aff.contains("\nLANG en_GB\n"), "{exported_aff}");
+        let expected_dic = "6\nwalk/ReSgCp\nfly/Sg\nLondon\nshit/Ns ph:shiit\nmay po:verb\nMay po:noun\n";
+        assert_eq!(exported_dic, expected_dic);
+        let reloaded = SpellLang::from_readers(
+            StrLineReader::new(TextParser::EXT_AFF, &exported_aff),
+            StrLineReader::new(TextParser::EXT_DIC, &exported_dic),
+        );
+        for word in ["walk", "rewalks", "walksed", "walkwalk", "flies", "flys", "London", "london", "shit", "walked"] {
+            assert_eq!(Spell::check_token(&reloaded, word), Spell::check_token(&spell_lang, word), "{word}");
+        }
+        assert_eq!(reloaded.export_aff(), exported_aff);
+        assert_eq!(reloaded.export_dic(), exported_dic);
+        assert_eq!(Spell::suggest(&reloaded, "wlaks"), Spell::suggest(&spell_lang, "wlaks"));
+    }
+
+    #[test]
+    fn default_flag_test() {
+        let aff = "SFX ö Y 1\nSFX ö 0 s .\nSFX é Y 1\nSFX é 0 ed .\n";
+        let dic = "2\nwalk/öé\ntalk/é\n";
+        let spell_lang = SpellLang::from_readers(
+            StrLineReader::new(TextParser::EXT_AFF, aff),
+            StrLineReader::new(TextParser::EXT_DIC, dic),
+        );
+        assert_eq!(spell_lang.slg_flag, FlagFormat::SingleChar);
+        assert_eq!(spell_lang.split_flags("öé"), vec!["ö", "é"]);
+        assert!(Spell::check_token(&spell_lang, "walks"));
+        assert!(Spell::check_token(&spell_lang, "talked"));
+        assert!(!Spell::check_token(&spell_lang, "talks"));
+        let mut text_parser = TextParser::new();
+        text_parser.tps_default_flag = FlagFormat::DoubleChar;
+        let spell_lang = text_parser.parse_lang_readers(
+            StrLineReader::new(TextParser::EXT_AFF, "SFX öé Y 1\nSFX öé 0 s .\n"),
+            StrLineReader::new(TextParser::EXT_DIC, "1\nwalk/öé\n"),
+        );
+        assert_eq!(spell_lang.slg_flag, FlagFormat::DoubleChar);
+        assert!(Spell::check_token(&spell_lang, "walks"));
+    }
+
+    #[test]
+    fn case_homonyms_test() {
+        let (text_parser, mut spell_lang) =
+            parse_neadic("SFX S Y 1\nSFX S 0 es .\nNEA DIC {\n    polish/S\n    Polish\n    May\n    may\n}\n");
+        assert!(!text_parser.tps_line_notes.iter().any(|note| note.contains("Duplicate")));
+        assert_eq!(spell_lang.slg_dic_hash["polish"].den_homonyms.len(), 1);
+        for word in ["polish", "polishes", "Polish", "POLISH", "may", "May"] {
+            assert!(Spell::check_token(&spell_lang, word), "{word}");
+        }
+        let mut words: Vec<String> = spell_lang.words().collect();
+        words.sort();
+        assert_eq!(words, vec!["May", "Polish", "may", "polish"]);
+        assert_eq!(spell_lang.completions("pol", 5), vec!["polish", "Polish"]);
+        // both homonyms are kept also in the compatible mode
+        spell_lang.slg_mode_flags = ModeFlag::TestCompat as u32;
+        assert!(Spell::check_token(&spell_lang, "polish"));
+        assert!(Spell::check_token(&spell_lang, "may"));
+        assert!(spell_lang.export_dic().contains("polish/S\nPolish\n"));
+        // without the lowercase word, the proper noun is not accepted in lowercase in the compatible mode
+        let (_, mut spell_lang) = parse_neadic("NEA DIC {\n    Polish\n}\n");
+        assert!(Spell::check_token(&spell_lang, "polish"));
+        spell_lang.slg_mode_flags = ModeFlag::TestCompat as u32;
+        assert!(Spell::check_token(&spell_lang, "Polish"));
+        assert!(!Spell::check_token(&spell_lang, "polish"));
+        // the flags of the homonym
+        let (_, spell_lang) = parse_neadic(
+            "ONLYINCOMPOUND O\nCOMPOUNDRULE 1\nCOMPOUNDRULE AB\nNEA DIC {\n    polish\n    Polish/AO\n    ware/B\n}\n",
+        );
+        assert_eq!(spell_lang.stems_with_flag("A").collect::<Vec<&str>>(), vec!["polish"]);
+        assert!(Spell::match_compound_rule(&spell_lang, &spell_lang.slg_compoundrule_items[0], &["polish", "ware"]));
+        assert_eq!(Spell::diagnose(&spell_lang, "polish").rji_reasons, vec![]);
+        assert_eq!(Spell::diagnose(&spell_lang, "Polish").rji_reasons, vec![RejectionReason::OnlyInCompound]);
+        assert_eq!(Spell::suggest(&spell_lang, "polosh"), vec!["polish"]);
+    }
+
+    #[test]
+    fn compound_min_test() {
+        let (_, spell_lang) = parse_neadic("SFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    cat/S\n}\n");
+        assert_eq!(spell_lang.compound_min(), 3);
+        let (_, spell_lang) = parse_neadic("COMPOUNDMIN 4\nSFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    cat/S\n}\n");
+        assert_eq!(spell_lang.compound_min(), 4);
+        // the surface part "cats" passes, although its stem "cat" is too short
+        assert!(Spell::check_token(&spell_lang, "cats"));
+        assert!(spell_lang.compound_part_fits_min("cats"));
+        assert!(!spell_lang.compound_part_fits_min("cat"));
+        assert!(spell_lang.compound_part_fits_min("čaša"));
+        // a two-character stem can't be a compound part with COMPOUNDMIN 3, a three-character one can
+        let (_, spell_lang) =
+            parse_neadic("COMPOUNDMIN 3\nCOMPOUNDFLAG Y\nNEA DIC {\n    ox/Y\n    cow/Y\n    cart/Y\n    horse/Y\n}\n");
+        assert!(Spell::check_token(&spell_lang, "horsecart"));
+        assert!(!Spell::check_token(&spell_lang, "oxcart"));
+        assert!(!Spell::check_token(&spell_lang, "cartox"));
+        assert!(Spell::check_token(&spell_lang, "cowcart"));
+        assert!(Spell::check_token(&spell_lang, "cartcow"));
+    }
+
+    #[test]
+    fn memory_estimate_test() {
+        let aff = "SFX A Y 2\nSFX A 0 s .\nSFX A 0 ed .\n";
+        let small_words: String = (0..10).map(|ix| format!("    walk{ix}/A\n")).collect();
+        let large_words: String = (0..1000).map(|ix| format!("    walk{ix}/A\n")).collect();
+        let (_, small_lang) = parse_neadic(&format!("{aff}NEA DIC {{\n{small_words}}}\n"));
+        let (_, large_lang) = parse_neadic(&format!("{aff}NEA DIC {{\n{large_words}}}\n"));
+        assert!(small_lang.memory_estimate() > 0);
+        assert!(large_lang.memory_estimate() > 50 * small_lang.memory_estimate());
+    }
+
+    #[test]
+    fn summary_test() {
+        let (_, spell_lang) = parse_neadic(
+            "SFX A Y 2\nSFX A 0 s .\nSFX A 0 ed .\nNEA DIC {\n    walk/A\n    talk/AZ\n    walk/A\n}\n",
+        );
+        let summary = spell_lang.summary();
+        assert_eq!(summary.smr_encoding, "UTF-8");
+        assert_eq!(summary.smr_flag_count, 1);
+        assert_eq!(summary.smr_affix_entry_count, 2);
+        assert_eq!(summary.smr_word_count, 2);
+        assert_eq!(summary.smr_duplicate_count, 1);
+        assert_eq!(summary.smr_unknown_flags.get("Z"), Some(&1));
+        assert_eq!(
+            Parser::get_summary(&spell_lang),
+            "encoding UTF-8, affixes 1/2, word entries 2, 1 duplicated entries, other flags Z*1."
+        );
+    }
+
+    #[test]
+    fn hyphenate_test() {
+        let patterns = ["hy3ph", "he2n", "hena4", "hen5at", "1na", "n2at", "1tio", "2io", "o2n"];
+        let (_, spell_lang) = parse_neadic(&format!("NEA HYPH {}\n", patterns.join(" ")));
+        assert_eq!(Parser::parse_hyph_pattern("hena4"), ("hena".to_string(), vec![0, 0, 0, 0, 4]));
+        assert_eq!(spell_lang.hyphenate("hyphenation"), vec![2, 6]);
+        assert_eq!(spell_lang.hyphenate("Hyphenation"), vec![2, 6]);
+        assert_eq!(spell_lang.syllable_count("hyphenation"), 3);
+        assert!(spell_lang.hyphenate("ha").is_empty());
+        assert!(spell_lang.export_aff().contains("NEA HYPH hy3ph he2n hena4 "));
+        // the patterns are not a tag of Hunspell
+        let (_, spell_lang) = parse_neadic("HYPH 1\nHYPH hy3ph\n");
+        assert!(Parser::is_unknown_tag(&spell_lang, "HYPH"));
+        assert!(spell_lang.hyphenate("hyphen").is_empty());
+        let (_, spell_lang) = parse_neadic("COMPOUNDSYLLABLE 6 aeiou\n");
+        assert_eq!(spell_lang.slg_comp_syllable, (6, "aeiou".to_string()));
+        assert!(spell_lang.hyphenate("hyphenation").is_empty());
+        assert_eq!(spell_lang.syllable_count("Hyphenation"), 4);
+    }
+
+    #[test]
+    fn compound_syllable_test() {
+        let dic = "COMPOUNDFLAG Y\nCOMPOUNDWORDMAX 2\nNEA DIC {\n    ball/Y\n    day/Y\n    game/Y\n}\n";
+        let (_, spell_lang) = parse_neadic(dic);
+        assert!(Spell::check_token(&spell_lang, "ballday"));
+        assert!(!Spell::check_token(&spell_lang, "balldaygame"));
+        // more words than COMPOUNDWORDMAX, but at most 4 syllables
+        let (_, spell_lang) = parse_neadic(&format!("COMPOUNDSYLLABLE 4 aeiou\n{dic}"));
+        assert!(spell_lang.compound_fits_syllable_max(4));
+        assert!(!spell_lang.compound_fits_syllable_max(5));
+        assert!(Spell::check_token(&spell_lang, "balldaygame"));
+        assert!(!Spell::check_token(&spell_lang, "gamegameball"));
+        assert!(!Spell::check_token(&spell_lang, "balldaygameday"));
+    }
+
+    #[test]
+    fn strict_unknown_tag_test() {
+        let text = "COMPOUNDSYLLABLE 6 aeiou\nCOMPUNDFLAG X\nSFX A Y 1\nSFX A 0 s .\n";
+        let mut text_parser = TextParser::new();
+        text_parser.tps_validating = true;
+        text_parser.tps_strict = true;
+        let mut spell_lang = SpellLang::new("test");
+        text_parser.parse_dictionary_text(&mut spell_lang, &mut StrLineReader::new(TextParser::EXT_NEADIC, text));
+        assert!(text_parser.tps_strict_failed);
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains(":2: Unknown tag")));
+        assert!(spell_lang.slg_aff_groups.is_empty());
+        assert!(Parser::is_unknown_tag(&spell_lang, "COMPUNDFLAG"));
+        assert!(!Parser::is_unknown_tag(&spell_lang, "COMPOUNDSYLLABLE"));
+    }
+
+    #[cfg(feature = "nfc")]
+    #[test]
+    fn condition_decomposed_test() {
+        // the stem is decomposed (e, combining acute), the conditions are composed or decomposed
+        let (_, spell_lang) = parse_neadic(
+            "SFX A Y 1\nSFX A 0 s \u{e9}\nSFX B Y 1\nSFX B 0 x e\u{301}\nNEA DIC {\n    cafe\u{301}/AB\n}\n",
+        );
+        assert!(Spell::check_token(&spell_lang, "cafe\u{301}s"));
+        assert!(Spell::check_token(&spell_lang, "cafe\u{301}x"));
+    }
+
+    #[test]
+    fn diff_accepted_test() {
+        let (_, base_lang) = parse_lang("en", "SFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    cat/S\n    dog/S\n    UNICEF\n}\n");
+        let (_, new_lang) = parse_lang("en", "SFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    cat/S\n    dog\n    UNICEF\n    cow/S\n}\n");
+        let mut words: Vec<String> = base_lang.words().chain(new_lang.words()).collect();
+        words.sort();
+        words.dedup();
+        assert_eq!(words, vec!["UNICEF", "cat", "cow", "dog"]);
+        words.push(String::from("dogs"));
+        let (accepted, rejected) = Spell::diff_accepted(&base_lang, &new_lang, &words);
+        assert_eq!(accepted, vec!["cow"]);
+        assert_eq!(rejected, vec!["dogs"]);
+    }
+
+    #[test]
+    fn dic_set_without_aff_test() {
+        let (mut text_parser, mut spell_lang) = parse_neadic("SFX A Y 1\nSFX A 0 s .\nNEA DIC {\n    word/A\n}\n");
+        // "šala" in ISO8859-2
+        let mut line_reader = StrLineReader::from_bytes(TextParser::EXT_DIC, b"SET ISO8859-2\n1\n\xb9ala/A\n");
+        text_parser.parse_supplement_dic(&mut spell_lang, &mut line_reader);
+        assert!(Spell::check_token(&spell_lang, "šala"));
+        assert!(Spell::check_token(&spell_lang, "šalas"));
+        assert!(!spell_lang.slg_dic_hash.contains_key("set"));
+        assert_eq!(spell_lang.slg_set, "UTF-8");
+    }
+
+    #[test]
+    fn repeated_notes_test() {
+        let mut text_parser = TextParser::new();
+        text_parser.tps_showing_details = true;
+        text_parser.tps_warn.insert(TextParser::SHOW_DIC_OTHER);
+        let mut spell_lang = SpellLang::new("test");
+        let dic = "4\none/Z\ntwo/Z\nthree/Z\nfour/Y\n";
+        text_parser.parse_dictionary_text(&mut spell_lang, &mut StrLineReader::new(TextParser::EXT_DIC, dic));
+        let unknown: Vec<&String> =
+            text_parser.tps_line_notes.iter().filter(|note| note.contains("Unknown flag")).collect();
+        assert_eq!(unknown.len(), 3);
+        assert!(unknown[0].contains(":2: Unknown flag"));
+        assert!(unknown[1].contains(":5: Unknown flag"));
+        assert!(unknown[2].ends_with("Unknown flag: Z (3 occurrences)"));
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.ends_with("Parse errors: 4")));
+    }
+
+    #[test]
+    fn run_tests_test() {
+        let test_report = TextParser::new().run_tests(
+            StrLineReader::new(TextParser::EXT_AFF, "SFX S Y 1\nSFX S 0 s .\n"),
+            StrLineReader::new(TextParser::EXT_DIC, "2\ncat/S\ndog\n"),
+            StrLineReader::new(TextParser::EXT_GOOD, "cat\ncats\ndogs\n"),
+            StrLineReader::new(TextParser::EXT_WRONG, "cow\ndog\n"),
+        );
+        assert_eq!(test_report.trp_passed, 3);
+        assert_eq!(test_report.trp_failed, 2);
+        assert_eq!(test_report.trp_rejected_good, vec!["dogs"]);
+        assert_eq!(test_report.trp_accepted_wrong, vec!["dog"]);
+        assert_eq!(test_report.trp_bad_gram_passed, None);
+        assert!(!test_report.all_passed());
+        let test_report = TextParser::new().run_neadic_tests(StrLineReader::new(
+            TextParser::EXT_NEADIC,
+            "NEA DIC {\n    cat\n}\nNEA TESTGOODWORDS {\n    cat\n}\nNEA TESTBADWORDS {\n    cats\n}\n",
+        ));
+        assert_eq!(test_report.trp_passed, 2);
+        assert!(test_report.all_passed());
+    }
+
+    #[test]
+    fn bundled_neadic_tests() {
+        let test_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests");
+        for dir_entry in std::fs::read_dir(test_dir).unwrap() {
+            let path = dir_entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != TextParser::EXT_NEADIC) {
+                continue;
+            }
+            let bytes = std::fs::read(&path).unwrap();
+            let line_reader = StrLineReader::from_bytes(TextParser::EXT_NEADIC, &bytes);
+            let mut text_parser = TextParser::new();
+            text_parser.tps_mode_flags = ModeFlag::TestCompat as u32;
+            let test_report = text_parser.run_neadic_tests(line_reader);
+            assert!(test_report.all_passed(), "{}: {:?}", path.display(), test_report);
+        }
+    }
+
+    #[test]
+    fn full_strip_test() {
+        let aff = "SFX A Y 2\nSFX A ab cd .\nSFX A 0 ab .\nPFX P Y 1\nPFX P ab ef .\n";
+        let dic = "NEA DIC {\n    ab/AP\n    a/A\n}\n";
+        let (_, spell_lang) = parse_neadic(&format!("{aff}{dic}"));
+        assert!(Spell::check_token(&spell_lang, "aab"));
+        assert!(!Spell::check_token(&spell_lang, "cd"));
+        assert!(!Spell::check_token(&spell_lang, "ef"));
+        let (_, spell_lang) = parse_neadic(&format!("FULLSTRIP\n{aff}{dic}"));
+        assert!(Spell::check_token(&spell_lang, "cd"));
+        assert!(Spell::check_token(&spell_lang, "ef"));
+        // words of the boundary lengths around the affixes don't panic
+        for word in ["", "a", "b", "ab", "ba", "abab", "cd", "c", "d", "e", "f", "ef", "čd", "ďab"] {
+            Spell::check_token(&spell_lang, word);
+        }
+    }
+
+    #[test]
+    fn compound_word_max_test() {
+        let (_, spell_lang) = parse_neadic("NEA DIC {\n    foot\n}\n");
+        assert!(spell_lang.compound_fits_word_max(10));
+        let (_, spell_lang) = parse_neadic("COMPOUNDWORDMAX 2\nNEA DIC {\n    foot\n}\n");
+        assert!(spell_lang.compound_fits_word_max(2));
+        assert!(!spell_lang.compound_fits_word_max(3));
+        let dic = "COMPOUNDFLAG Y\nNEA DIC {\n    foot\n    ball/Y\n    game/Y\n    day/Y\n}\n";
+        let (text_parser, spell_lang) = parse_neadic(&format!("COMPOUNDWORDMAX 0\n{dic}"));
+        assert!(Spell::check_token(&spell_lang, "balldaygameday"));
+        assert!(text_parser.tps_line_notes.iter().any(|note| note.contains("No limit of compound words")));
+        let (_, spell_lang) = parse_neadic(&format!("COMPOUNDWORDMAX 3\n{dic}"));
+        assert!(Spell::check_token(&spell_lang, "balldaygame"));
+        assert!(!Spell::check_token(&spell_lang, "balldaygameday"));
+        let (_, spell_lang) = parse_neadic(&format!("COMPOUNDWORDMAX 2\n{dic}"));
+        assert!(Spell::check_token(&spell_lang, "ballday"));
+        assert!(!Spell::check_token(&spell_lang, "balldaygame"));
+    }
+}
@@ -4,10 +4,16 @@
 // The option names and the variable names are defined here.
 
 use neaspell_core::core_speller;
+use neaspell_core::core_speller::AffixUsage;
+use neaspell_core::core_speller::FlagFormat;
 use neaspell_core::core_speller::SpellLang;
 use neaspell_core::core_speller::TokenType;
+use neaspell_core::core_speller::Tokenizer;
+use neaspell_core::core_speller::HashSet;
+use neaspell_core::core_speller::HashMap;
 use neaspell_core::text_parser;
 use neaspell_core::text_parser::LineReader;
+use core_speller::CheckFeature;
 use core_speller::ModeFlag;
 use core_speller::Spell;
 use neaspell_core::text_parser::Parser;
@@ -18,7 +24,7 @@ use std::fs::OpenOptions;
 use std::io::stdout;
 use std::io::BufWriter;
 use std::io::{self, prelude::*, BufReader};
-use std::path::{MAIN_SEPARATOR, MAIN_SEPARATOR_STR};
+use std::path::{Path, MAIN_SEPARATOR, MAIN_SEPARATOR_STR};
 use std::str;
 use text_parser::TextParser;
 
@@ -78,7 +84,7 @@ impl ArgTokens {
 struct StdLineReader {
     pub slr_base_name: String,
     pub slr_extension: String,
-    slr_reader: Option<BufReader<File>>
+    slr_reader: Option<Box<dyn BufRead>>
 }
 
 impl StdLineReader {
@@ -89,7 +95,7 @@ impl StdLineReader {
             return StdLineReader {
                 slr_base_name: slr_base_name.to_string(),
                 slr_extension: slr_extension.to_string(),
-                slr_reader: Some(BufReader::new(file))};
+                slr_reader: Some(Box::new(BufReader::new(file)))};
         }
         return StdLineReader {
             slr_base_name: slr_base_name.to_string(),
@@ -97,6 +103,15 @@ impl StdLineReader {
             slr_reader:None
         }
     }
+
+    /// Reader of a neadic text from any source, e.g. the standard input.
+    pub fn from_reader(slr_base_name: &str, reader: Box<dyn BufRead>) -> StdLineReader {
+        StdLineReader {
+            slr_base_name: slr_base_name.to_string(),
+            slr_extension: TextParser::EXT_NEADIC.to_string(),
+            slr_reader: Some(reader),
+        }
+    }
 }
 
 impl LineReader for StdLineReader {
@@ -118,12 +133,121 @@ impl LineReader for StdLineReader {
     }
 }
 
+/// The misspelled words of one checked text file, see option --summary-per-file.
+pub struct FileSummary {
+    pub fsm_name: String,
+    pub fsm_bad_count: usize, // all the misspelled words, also the repeated ones
+    pub fsm_bad_words: HashMap<String, usize>, // the count of each misspelled word
+}
+
+impl FileSummary {
+    /// How many of the most frequent misspelled words are shown.
+    pub const TOP_WORDS: usize = 5;
+
+    pub fn new(fsm_name: &str) -> FileSummary {
+        FileSummary {
+            fsm_name: fsm_name.to_string(),
+            fsm_bad_count: 0,
+            fsm_bad_words: HashMap::default(),
+        }
+    }
+
+    /// Counts the misspelled words of the checked tokens.
+    pub fn add_tokens(&mut self, checked_tokens: &[(String, TokenType, Option<&str>)]) {
+        for (word, token_type, _) in checked_tokens {
+            if *token_type == TokenType::IsBadWord {
+                self.fsm_bad_count += 1;
+                *self.fsm_bad_words.entry(word.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Returns the most frequent misspelled words with their counts,
+    /// the words of the same count in the alphabetical order.
+    pub fn top_words(&self, limit: usize) -> Vec<(&str, usize)> {
+        let mut words: Vec<(&str, usize)> =
+            self.fsm_bad_words.iter().map(|(word, count)| (word.as_str(), *count)).collect();
+        words.sort_by(|(word1, count1), (word2, count2)| count2.cmp(count1).then(word1.cmp(word2)));
+        words.truncate(limit);
+        words
+    }
+
+    /// Writes the file name, the count of the misspelled words and the most frequent of them,
+    /// e.g. "a.txt: 3 misspelled: teh (2), wrod (1)".
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        let top_words: Vec<String> = self
+            .top_words(Self::TOP_WORDS)
+            .iter()
+            .map(|(word, count)| format!("{word} ({count})"))
+            .collect();
+        if top_words.is_empty() {
+            writeln!(writer, "{}: {} misspelled", self.fsm_name, self.fsm_bad_count)
+        } else {
+            writeln!(writer, "{}: {} misspelled: {}", self.fsm_name, self.fsm_bad_count, top_words.join(", "))
+        }
+    }
+}
+
+/// The dictionaries of a directory by their language codes,
+/// e.g. for a server checking the texts in many languages.
+pub struct DictRegistry {
+    drg_langs: HashMap<String, SpellLang>,
+    pub drg_line_notes: Vec<String>, // the notes of parsing the dictionaries
+}
+
+impl DictRegistry {
+    /// Loads the dictionaries of the .aff and .dic file pairs in the directory.
+    /// The language code is the file name without the extension, e.g. "en_US" for "en_US.aff".
+    pub fn load_dir(path: &str) -> DictRegistry {
+        let mut drg_langs: HashMap<String, SpellLang> = HashMap::default();
+        let mut text_parser = TextParser::new();
+        let aff_suffix = format!(".{}", TextParser::EXT_AFF);
+        let aff_names = CliSpeller::list_wildcarded(&format!("{path}{MAIN_SEPARATOR}*{aff_suffix}"));
+        for aff_name in aff_names {
+            let Some(base_file_name) = aff_name.strip_suffix(&aff_suffix) else {
+                continue;
+            };
+            if !Path::new(&format!("{base_file_name}.{}", TextParser::EXT_DIC)).exists() {
+                continue;
+            }
+            let lang_code = base_file_name.rsplit(MAIN_SEPARATOR).next().unwrap_or(base_file_name);
+            let mut spell_lang = text_parser.new_spell_lang(lang_code);
+            for file_ext in [TextParser::EXT_AFF, TextParser::EXT_DIC] {
+                let mut std_line_reader = StdLineReader::new(base_file_name, file_ext);
+                text_parser.parse_dictionary_text(&mut spell_lang, &mut std_line_reader);
+            }
+            drg_langs.insert(lang_code.to_string(), spell_lang);
+        }
+        DictRegistry { drg_langs, drg_line_notes: std::mem::take(&mut text_parser.tps_line_notes) }
+    }
+
+    /// Returns the dictionary of the language code.
+    pub fn get(&self, lang_code: &str) -> Option<&SpellLang> {
+        self.drg_langs.get(lang_code)
+    }
+
+    /// Returns the sorted language codes of the loaded dictionaries.
+    pub fn codes(&self) -> Vec<&str> {
+        let mut codes: Vec<&str> = self.drg_langs.keys().map(|code| code.as_str()).collect();
+        codes.sort();
+        codes
+    }
+}
+
 pub struct CliSpeller {
     csr_arg_tokens: ArgTokens,
     csr_dict_codes: String, // comma-separated dictionary codes, possibly with asterisk wildcards, or
     // paths (with separators) to the dictionary files, without the file extension
     csr_test_codes: Vec<String>, // names or test files, possibly with asterisk wildcards
     csr_test_words: String, // comma-separated test word, to filter-out the other words
+    csr_list_flag: Option<String>, // list the words with this flag
+    csr_test_flags: Vec<(String, String)>, // word and flag, to report whether the word has the flag
+    csr_diff: Option<(String, String)>, // base and new dictionary, to compare the accepted words
+    csr_jsonl: bool, // output one JSON object per checked line
+    csr_positions: bool, // output the misspelled words with the file name, line and column
+    csr_unused_affixes: bool, // output the affix classes not used by the words of the text files
+    csr_summary_per_file: bool, // after checking, output the misspelled word counts of each text file
+    csr_guess_lang: bool, // select the language for each sentence of mixed-language text
     csr_text_files: Vec<String>,
     csr_options_finished: bool, // true after "--" argument
 
@@ -135,6 +259,8 @@ pub struct CliSpeller {
     pub spl_strict_slash: bool,
     /// search directories for the tests
     pub spl_test_paths: Vec<String>,
+    /// notes about the search directories that don't exist, shown in detail mode
+    pub spl_path_notes: Vec<String>,
     pub spl_out_file_name: Option<String>,
     pub spl_out_writer: Option<Box<dyn Write>>,
 }
@@ -148,22 +274,60 @@ impl CliSpeller {
             csr_dict_codes: String::new(),
             csr_test_codes: vec![],
             csr_test_words: String::new(),
+            csr_list_flag: None,
+            csr_test_flags: vec![],
+            csr_diff: None,
+            csr_jsonl: false,
+            csr_positions: false,
+            csr_unused_affixes: false,
+            csr_summary_per_file: false,
+            csr_guess_lang: false,
             csr_text_files: vec![],
             csr_options_finished: false,
 
             spl_dic_paths: vec![],
             spl_strict_slash: false,
             spl_test_paths: vec![],
+            spl_path_notes: vec![],
             spl_out_file_name: None,
             spl_out_writer: None,
         }
     }
 
+    /// Returns the note if the directory of the search path (before the wildcard)
+    /// doesn't exist or isn't a directory, e.g. for a typo in NEA_DICPATH.
+    pub fn check_search_dir(var_name: &str, path_wildcarded: &str) -> Option<String> {
+        let dir = if path_wildcarded.contains("*") {
+            match path_wildcarded.rsplit_once(MAIN_SEPARATOR) {
+                Some((dir, _)) => dir,
+                None => ".",
+            }
+        } else {
+            path_wildcarded
+        };
+        let path = Path::new(dir);
+        if !path.exists() {
+            Some(format!("{var_name}: Directory not found: {dir}"))
+        } else if !path.is_dir() {
+            Some(format!("{var_name}: Not a directory: {dir}"))
+        } else {
+            None
+        }
+    }
+
     /// Returns true if the environment variable exists.
-    pub fn process_path_environment_variable(var_name: &str, var_vec: &mut Vec<String>) -> bool {
+    /// The notes about the missing directories are added to path_notes.
+    pub fn process_path_environment_variable(
+        var_name: &str,
+        var_vec: &mut Vec<String>,
+        path_notes: &mut Vec<String>,
+    ) -> bool {
         if let Some(paths) = env::var_os(var_name) {
             for dic_path in env::split_paths(&paths) {
                 let path_wildcarded = dic_path.into_os_string().into_string().unwrap();
+                if let Some(note) = Self::check_search_dir(var_name, &path_wildcarded) {
+                    path_notes.push(note);
+                }
                 let entry_vec = Self::list_wildcarded(&path_wildcarded);
                 for entry in entry_vec {
                     var_vec.push(entry);
@@ -178,7 +342,7 @@ impl CliSpeller {
     const COMMON_DICPATH: &'static str = "DICPATH";
     const NEA_TESTPATH: &'static str = "NEA_TESTPATH";
     /// Process environment variable, e.g.
-    /// ```
+    /// ```text
     /// $Env:NEA_DICPATH=".;C:\0prog\spelling\dictionaries\*"
     /// export NEA_DICPATH='.:/mnt/c/0prog/spelling/dictionaries/*'
     /// $Env:NEA_TESTPATH=".;C:\0prog\spelling\tests"
@@ -190,13 +354,16 @@ impl CliSpeller {
         let _ = CliSpeller::process_path_environment_variable(
             Self::NEA_DICPATH,
             &mut self.spl_dic_paths,
+            &mut self.spl_path_notes,
         ) || CliSpeller::process_path_environment_variable(
             Self::COMMON_DICPATH,
             &mut self.spl_dic_paths,
+            &mut self.spl_path_notes,
         );
         let _ = CliSpeller::process_path_environment_variable(
             Self::NEA_TESTPATH,
             &mut self.spl_test_paths,
+            &mut self.spl_path_notes,
         );
     }
 
@@ -215,7 +382,7 @@ impl CliSpeller {
         while let Some(arg) = self.csr_arg_tokens.get_next_arg() {
             if arg == "--strict-slash" {
                 text_parser.tps_skip_output = true;
-            } else if self.csr_options_finished || !arg.starts_with("-") {
+            } else if self.csr_options_finished || !arg.starts_with("-") || arg == "-" {
                 self.csr_text_files.push(arg.clone());
             } else if arg == "-d" {
                 // compatible: dictionary name
@@ -235,15 +402,61 @@ impl CliSpeller {
                 }
             } else if arg == "--test-word" {
                 if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
-                    // test name "*" matches all aff files in any search directory
+                    // only the matching test words, e.g. "pre*,!preview", see test_word_selected
                     if self.csr_test_words.len() != 0 {
                         self.csr_test_words += ",";
                     }
                     self.csr_test_words += &arg_value;
                 }
                 //
+            } else if arg == "--list-flagged" {
+                // list the dictionary words having the flag, e.g. NOSUGGEST flag
+                if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
+                    self.csr_list_flag = Some(arg_value);
+                }
+            } else if arg == "--test-flag" {
+                // report whether the dictionary has the word with the flag, e.g. walk:G
+                if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
+                    if let Some((word, flag)) = arg_value.rsplit_once(':') {
+                        self.csr_test_flags.push((word.to_string(), flag.to_string()));
+                    } else {
+                        println!("Expected WORD:FLAG for argument: {arg} {arg_value}");
+                    }
+                }
+            } else if arg == "--diff" {
+                // compare two dictionaries, on the words of the text files or of both dictionaries
+                if let Some(base_code) = self.csr_arg_tokens.get_arg_option() {
+                    if let Some(new_code) = self.csr_arg_tokens.get_arg_option() {
+                        self.csr_diff = Some((self.normalize_path(&base_code), self.normalize_path(&new_code)));
+                    }
+                }
             } else if arg == "--compat" {
                 text_parser.tps_mode_flags |= ModeFlag::TestCompat as u32;
+            } else if arg == "--identifiers" {
+                // underscore is a word character, e.g. snake_case_word is one token
+                text_parser.tps_mode_flags |= ModeFlag::Identifiers as u32;
+            } else if arg == "--normalize-quotes" {
+                // check don’t (typographic apostrophe) as don't
+                text_parser.tps_mode_flags |= ModeFlag::NormalizeQuotes as u32;
+            } else if arg == "--features" {
+                // the enabled (+) and disabled (-) features, e.g. +compound,-ngram
+                if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
+                    for item in arg_value.split(',') {
+                        let (enabled, name) = match item.strip_prefix('-') {
+                            Some(name) => (false, name),
+                            None => (true, item.trim_start_matches('+')),
+                        };
+                        if let Some(feature) = CheckFeature::from_name(name) {
+                            if enabled {
+                                text_parser.tps_features |= feature as u32;
+                            } else {
+                                text_parser.tps_features &= !(feature as u32);
+                            }
+                        } else {
+                            println!("Unknown feature: {arg} {item}");
+                        }
+                    }
+                }
             } else if arg == "-D" {
                 text_parser.tps_showing_details = true;
             } else if arg == "-q" {
@@ -251,6 +464,21 @@ impl CliSpeller {
             } else if arg == "-l" {
                 // compatible: list incorrect words
                 text_parser.tps_check_level = 1;
+            } else if arg == "--jsonl" {
+                // JSON object for each line, written as soon as the line is checked
+                self.csr_jsonl = true;
+            } else if arg == "--positions" {
+                // each misspelled word as file:line:column: word
+                self.csr_positions = true;
+            } else if arg == "--unused-affixes" {
+                // the affix classes not used by any accepted word of the text files
+                self.csr_unused_affixes = true;
+            } else if arg == "--summary-per-file" {
+                // after checking, each text file with its count of misspelled words
+                self.csr_summary_per_file = true;
+            } else if arg == "--guess-lang" {
+                // with several dictionaries, check each sentence with the best-matching one
+                self.csr_guess_lang = true;
             } else if arg == "-a" {
                 // compatible: all output, report incorrect words with suggestions
                 text_parser.tps_check_level = 2;
@@ -262,8 +490,76 @@ impl CliSpeller {
             } else if arg == "--max-notes" {
                 // maximal number of notes per category
                 if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
-                    text_parser.tps_max_notes = arg_value.parse::<u32>().unwrap();
+                    if let Ok(max_notes) = arg_value.parse::<u32>() {
+                        text_parser.tps_max_notes = max_notes;
+                    } else {
+                        println!("Expected number for argument: {arg} {arg_value}");
+                    }
+                }
+            } else if arg == "--suggest-max" || arg == "--max-suggestions" {
+                // maximal number of suggestions for a misspelled word, by default 5,
+                // the phonetic and n-gram suggestions are limited by MAXNGRAMSUGS, too
+                if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
+                    if let Ok(suggest_max) = arg_value.parse::<u32>() {
+                        text_parser.tps_suggest_max = suggest_max;
+                    } else {
+                        println!("Expected number for argument: {arg} {arg_value}");
+                    }
+                }
+            } else if arg == "--max-word-length" {
+                // maximal length of a checked word, the longer tokens aren't checked
+                if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
+                    if let Ok(word_len_max) = arg_value.parse::<usize>() {
+                        text_parser.tps_word_len_max = word_len_max;
+                    } else {
+                        println!("Expected number for argument: {arg} {arg_value}");
+                    }
+                }
+            } else if arg == "--skip-pattern" {
+                // the tokens matching the pattern aren't checked, e.g. version numbers "v\d+"
+                if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
+                    let skip_pattern = core_speller::SkipPattern::new(&arg_value);
+                    if let Some((desc, column)) = skip_pattern.skp_error {
+                        println!("{desc}: {arg} {arg_value}, column {column}");
+                    } else {
+                        text_parser.tps_skip_patterns.push(skip_pattern);
+                    }
+                }
+            } else if arg == "--continue-loading" {
+                // when the memory for dictionary can't be reserved, continue loading without reservation
+                text_parser.tps_continue_loading = true;
+            } else if arg == "--ignore-flags" {
+                // comma-separated flags to remove from the dictionary words, e.g. of unsupported tags
+                if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
+                    for flag in arg_value.split(',').filter(|flag| !flag.is_empty()) {
+                        text_parser.tps_ignore_flags.push(flag.to_string());
+                    }
+                }
+            } else if arg == "--keyboard" {
+                // the keyboard layout for ordering the suggestions, instead of KEY of the dictionary
+                if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
+                    if SpellLang::KEYBOARD_LAYOUTS.iter().any(|(name, _)| *name == arg_value) {
+                        text_parser.tps_keyboard = arg_value;
+                    } else {
+                        println!("Unknown keyboard layout: {arg_value}");
+                    }
+                }
+            } else if arg == "--default-flag" {
+                // the format of the flags in the dictionaries without FLAG tag: UTF-8, long or num
+                if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
+                    if let Some(flag_format) = FlagFormat::from_tag_value(&arg_value) {
+                        text_parser.tps_default_flag = flag_format;
+                    } else {
+                        println!("Unknown flag format: {arg_value}");
+                    }
                 }
+            } else if arg == "--validate" {
+                // additional checks of the dictionary
+                text_parser.tps_validating = true;
+            } else if arg == "--strict" {
+                // with --validate, an unknown tag stops loading and the exit status is failure
+                text_parser.tps_validating = true;
+                text_parser.tps_strict = true;
             } else if arg == "--warn" {
                 if let Some(arg_value) = self.csr_arg_tokens.get_arg_option() {
                     for show_id in arg_value.split(',') {
@@ -421,6 +717,16 @@ impl CliSpeller {
         ext_code_vec
     }
 
+    /// Writes the stored notes to the output and clears them.
+    fn write_line_notes(&mut self, text_parser: &mut TextParser) {
+        if let Some(writer) = &mut self.spl_out_writer {
+            for line_note in &text_parser.tps_line_notes {
+                let _ = writeln!(writer, "{line_note}");
+            }
+        }
+        text_parser.tps_line_notes.clear();
+    }
+
     /// Reads the dictionary for the 'lang_code'. 'base_file_name' is nearly full file name, it's only missing file extension.
     pub fn read_lang_single(
         &mut self,
@@ -429,8 +735,7 @@ impl CliSpeller {
         base_file_name: String,
         including_tests: bool, 
     ) {
-        let mut spell_lang = SpellLang::new(lang_code);
-        spell_lang.slg_mode_flags = text_parser.tps_mode_flags;
+        let mut spell_lang = text_parser.new_spell_lang(lang_code);
         let ext_count: u32 = if including_tests {4} else {2}; // after so many loaded files, loading can stop
         let ext_vec = [TextParser::EXT_AFF, TextParser::EXT_DIC, TextParser::EXT_GOOD, TextParser::EXT_WRONG, TextParser::EXT_NEADIC];
 
@@ -450,12 +755,7 @@ impl CliSpeller {
                 let mut std_line_reader= StdLineReader::new (&base_file_name, file_ext);
                 if std_line_reader.slr_reader.is_some() {
                     text_parser.parse_dictionary_text(&mut spell_lang, &mut std_line_reader);
-                    if let Some(writer) = &mut self.spl_out_writer {
-                        for line_note in &text_parser.tps_line_notes {
-                            let _ = writeln!(writer, "{line_note}");
-                        }
-                    }
-                    text_parser.tps_line_notes.clear();
+                    self.write_line_notes(text_parser);
                     true
                 } else {
                     false
@@ -492,11 +792,55 @@ impl CliSpeller {
         text_parser.tps_langs.push(spell_lang);
     }
 
+    /// Reads the dic file without aff file (e.g. de_med) as a supplement of the previously
+    /// loaded language. 'base_file_name' is nearly full file name, it's only missing file extension.
+    /// Without a previously loaded language, only a note is written.
+    pub fn read_lang_supplement(&mut self, text_parser: &mut TextParser, base_file_name: String) {
+        let Some(mut spell_lang) = text_parser.tps_langs.pop() else {
+            text_parser.store_note(&format!("{base_file_name}.{}: No language to supplement", TextParser::EXT_DIC));
+            self.write_line_notes(text_parser);
+            return;
+        };
+        let mut std_line_reader = StdLineReader::new(&base_file_name, TextParser::EXT_DIC);
+        text_parser.parse_supplement_dic(&mut spell_lang, &mut std_line_reader);
+        if text_parser.tps_showing_details {
+            text_parser.store_noline_note(
+                &spell_lang.slg_code,
+                TextParser::EXT_DIC,
+                &Parser::get_summary(&spell_lang),
+            );
+        }
+        self.write_line_notes(text_parser);
+        text_parser.tps_langs.push(spell_lang);
+    }
+
+    /// Reads the dictionary in the neadic format, with the affixes and the words
+    /// in the sections (NEA DIC), e.g. from the standard input.
+    pub fn read_lang_reader(&mut self, text_parser: &mut TextParser, lang_code: &str, reader: Box<dyn BufRead>) {
+        let mut spell_lang = text_parser.new_spell_lang(lang_code);
+        let mut std_line_reader = StdLineReader::from_reader(lang_code, reader);
+        text_parser.parse_dictionary_text(&mut spell_lang, &mut std_line_reader);
+        self.write_line_notes(text_parser);
+        if text_parser.tps_showing_details {
+            text_parser.store_noline_note(
+                lang_code,
+                TextParser::EXT_NEADIC,
+                &Parser::get_summary(&spell_lang),
+            );
+        }
+        text_parser.tps_langs.push(spell_lang);
+    }
+
     /// Reads the dictionaries for the 'lang_code', e.g.
     /// "es*", "de_AT" or "*" or "de_med" or "../dict/de_CH".
     /// Slashes (/) or backslashes (\) are to be used depending on OS.
-    /// todo if the aff file is missing (case: de_med), take the dictionary as extending the previous one
+    /// If the aff file is missing (case: de_med), the dictionary is extending the previous one.
+    /// The code "-" is the neadic dictionary from the standard input.
     pub fn read_lang_ext(&mut self, text_parser: &mut TextParser, lang_code_ext: &str) {
+        if lang_code_ext == Self::STDIN_NAME {
+            self.read_lang_reader(text_parser, "stdin", Box::new(io::stdin().lock()));
+            return;
+        }
         let ext_code_vec: Vec<String> = self.expand_dict_file_name(lang_code_ext);
         for ext_code in ext_code_vec {
             let (dir, name_after_delim) = ext_code.rsplit_once(MAIN_SEPARATOR).unwrap();
@@ -508,56 +852,335 @@ impl CliSpeller {
             } else {
                 format!("{}", lang_parts[0])
             };
+            let aff_name = format!("{}.{}", base_file_name, TextParser::EXT_AFF);
+            let dic_name = format!("{}.{}", base_file_name, TextParser::EXT_DIC);
+            if !Path::new(&aff_name).exists()
+                && Path::new(&dic_name).exists()
+                && !text_parser.tps_langs.is_empty()
+            {
+                self.read_lang_supplement(text_parser, base_file_name);
+                continue;
+            }
             _ = self.read_lang_single(text_parser, &lang_code, base_file_name, false);
         }
     }
 
     /// Check several words or paragraph, not yet tokenized.
-    /// The language (in the current code) is not yet known, several can be tried
-    pub fn check_text(&self, text_parser: &mut TextParser, untokenized: &str) {
-        for lang in &text_parser.tps_langs {
-            // todo let each tokenization take only one token, not all
-            // then it'll be possible to try languages in sequence until one succeeds
-            let checked_tokens = Spell::check_text(&lang, untokenized);
-            // todo depending on spl_check_level, let the function return more info
-            for (word, token_type) in &checked_tokens {
-                if word.len() == 0 {
-                    continue;
-                }
-                if *token_type != TokenType::IsGoodWord && *token_type != TokenType::IsBadWord {
-                    continue;
-                }
-                if !text_parser.tps_skip_output {
-                    if text_parser.tps_check_level > 1 {
-                        if *token_type == TokenType::IsGoodWord {
-                            println!("*");
+    /// The language (in the current code) is not yet known, several can be tried.
+    /// The dictionaries are tried in the order of the -d options, a word is good
+    /// if any of them accepts it. With -D, the accepting dictionary is shown.
+    /// With -a, a misspelled word is written as in hunspell, with the number of suggestions,
+    /// the byte offset of the word in the line and the suggestions of all the dictionaries,
+    /// at most tps_suggest_max,
+    /// e.g. "& dgo 2 8: dog, ego", or without suggestions as "# dgo 8".
+    pub fn check_text(&self, text_parser: &TextParser, untokenized: &str, writer: &mut dyn Write) -> io::Result<()> {
+        self.write_checked_text(text_parser, &self.check_text_tokens(text_parser, untokenized), writer)
+    }
+
+    /// Writes the tokens checked by check_text_tokens, see check_text.
+    fn write_checked_text(
+        &self,
+        text_parser: &TextParser,
+        checked_tokens: &[(String, TokenType, Option<&str>)],
+        writer: &mut dyn Write,
+    ) -> io::Result<()> {
+        let mut offset: usize = 0;
+        for (word, token_type, lang_code) in checked_tokens {
+            let word_offset = offset;
+            offset += word.len();
+            if word.is_empty() {
+                continue;
+            }
+            if *token_type != TokenType::IsGoodWord && *token_type != TokenType::IsBadWord {
+                continue;
+            }
+            if !text_parser.tps_skip_output {
+                if text_parser.tps_check_level > 1 {
+                    if *token_type == TokenType::IsGoodWord {
+                        if text_parser.tps_showing_details {
+                            writeln!(writer, "* {}", lang_code.unwrap_or(""))?;
                         } else {
-                            println!("& {}", &word);
+                            writeln!(writer, "*")?;
                         }
                     } else {
-                        if *token_type == TokenType::IsGoodWord {
-                            // nothing to do
+                        let mut suggestions: Vec<String> = vec![];
+                        for spell_lang in &text_parser.tps_langs {
+                            for suggestion in Spell::suggest(spell_lang, word) {
+                                if suggestions.len() < text_parser.tps_suggest_max as usize
+                                    && !suggestions.contains(&suggestion)
+                                {
+                                    suggestions.push(suggestion);
+                                }
+                            }
+                        }
+                        if suggestions.is_empty() {
+                            writeln!(writer, "# {word} {word_offset}")?;
                         } else {
-                            println!("{}", &word);
+                            writeln!(writer, "& {word} {} {word_offset}: {}", suggestions.len(), suggestions.join(", "))?;
                         }
-                    };
+                    }
+                } else {
+                    if *token_type == TokenType::IsGoodWord {
+                        // nothing to do
+                    } else {
+                        writeln!(writer, "{}", &word)?;
+                    }
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the text with all the loaded dictionaries, see option --guess-lang.
+    fn check_text_tokens<'l>(
+        &self,
+        text_parser: &'l TextParser,
+        untokenized: &str,
+    ) -> Vec<(String, TokenType, Option<&'l str>)> {
+        if self.csr_guess_lang {
+            Spell::check_text_guess_langs(&text_parser.tps_langs, untokenized)
+        } else {
+            Spell::check_text_langs(&text_parser.tps_langs, untokenized)
+        }
+    }
+
+    /// Outputs the sorted stems having the flag, directly or by the flag alias (AF).
+    pub fn list_flagged(&self, spell_lang: &SpellLang, flag: &str) {
+        let mut stems: Vec<&str> = spell_lang.stems_with_flag(flag).collect();
+        stems.sort();
+        for stem in stems {
+            println!("{stem}");
+        }
+    }
+
+    /// Outputs for each word and flag whether the dictionary has the word with the flag.
+    pub fn test_flags(&self, spell_lang: &SpellLang) {
+        for (word, flag) in &self.csr_test_flags {
+            println!("{}:{} {}", word, flag, spell_lang.has_word_with_flag(word, flag));
+        }
+    }
+
+    /// Loads the two dictionaries of option --diff and outputs the words
+    /// newly accepted (+word) and newly rejected (-word) by the new dictionary.
+    /// The words are from the text files or, without them, from both dictionaries.
+    pub fn diff_dictionaries(&mut self, text_parser: &mut TextParser, base_code: &str, new_code: &str) {
+        let other_langs = std::mem::take(&mut text_parser.tps_langs);
+        self.read_lang_ext(text_parser, base_code);
+        let base_langs = std::mem::take(&mut text_parser.tps_langs);
+        self.read_lang_ext(text_parser, new_code);
+        let new_langs = std::mem::replace(&mut text_parser.tps_langs, other_langs);
+        self.write_line_notes(text_parser);
+        let (Some(base_lang), Some(new_lang)) = (base_langs.last(), new_langs.last()) else {
+            return;
+        };
+        let mut words: Vec<String> = vec![];
+        if self.csr_text_files.is_empty() {
+            words.extend(base_lang.words().chain(new_lang.words()));
+            words.sort();
+            words.dedup();
+        } else {
+            let mut seen: HashSet<String> = HashSet::default();
+            for text_name in &self.csr_text_files {
+                let Ok(text) = fs::read_to_string(text_name) else {
+                    println!("Could not read: {text_name}");
+                    continue;
+                };
+                for (word, token_type) in Tokenizer::from_lang(base_lang).tokenize(&text) {
+                    if token_type == TokenType::IsWord && seen.insert(word.clone()) {
+                        words.push(word);
+                    }
                 }
-                //println!("Word {}: {}", String::from(result_s), word);
             }
         }
+        let (accepted, rejected) = Spell::diff_accepted(base_lang, new_lang, &words);
+        for word in accepted {
+            println!("+{word}");
+        }
+        for word in rejected {
+            println!("-{word}");
+        }
     }
 
-    pub fn check_text_file(&self, text_parser: &mut TextParser, text_name: &String) -> io::Result<()> {
-        let file = File::open(text_name.clone())?;
-        let reader = BufReader::new(file);
-        for line in reader.lines() {
-            let untokenized = line?;
-            self.check_text(text_parser, &untokenized);
+    /// Checks the words of the texts and outputs the affix classes not used by any accepted word,
+    /// one per line, see option --unused-affixes.
+    pub fn write_unused_affixes(
+        &self,
+        spell_lang: &SpellLang,
+        texts: &[String],
+        writer: &mut impl Write,
+    ) -> io::Result<()> {
+        let mut affix_usage = AffixUsage::new(spell_lang);
+        for text in texts {
+            for (word, token_type) in Tokenizer::from_lang(spell_lang).tokenize(text) {
+                if token_type == TokenType::IsWord {
+                    affix_usage.check_token(spell_lang, &word);
+                }
+            }
+        }
+        for class_name in affix_usage.unused_classes(spell_lang) {
+            writeln!(writer, "{class_name}")?;
+        }
+        Ok(())
+    }
+
+    /// Returns the string as JSON string literal, with quotes.
+    pub fn json_string(s: &str) -> String {
+        let mut json = String::with_capacity(s.len() + 2);
+        json.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => json += "\\\"",
+                '\\' => json += "\\\\",
+                '\n' => json += "\\n",
+                '\r' => json += "\\r",
+                '\t' => json += "\\t",
+                c if (c as u32) < 0x20 => json += &format!("\\u{:04x}", c as u32),
+                c => json.push(c),
+            }
+        }
+        json.push('"');
+        json
+    }
+
+    /// Checks one line of text and writes the result as a JSON object on a single line,
+    /// e.g. {"line":2,"bad":[{"word":"Fibula","offset":15}]}
+    /// The offset is the byte position of the word in the line.
+    /// The writer is flushed, so that the consumer can process each line immediately.
+    pub fn check_text_jsonl(
+        &self,
+        text_parser: &TextParser,
+        line_no: usize,
+        untokenized: &str,
+        writer: &mut dyn Write,
+    ) -> io::Result<()> {
+        Self::write_checked_jsonl(line_no, &self.check_text_tokens(text_parser, untokenized), writer)
+    }
+
+    /// Writes the tokens checked by check_text_tokens, see check_text_jsonl.
+    fn write_checked_jsonl(
+        line_no: usize,
+        checked_tokens: &[(String, TokenType, Option<&str>)],
+        writer: &mut dyn Write,
+    ) -> io::Result<()> {
+        let mut bad_words: Vec<String> = vec![];
+        let mut offset: usize = 0;
+        for (word, token_type, _lang_code) in checked_tokens {
+            if *token_type == TokenType::IsBadWord {
+                bad_words.push(format!(
+                    "{{\"word\":{},\"offset\":{offset}}}",
+                    Self::json_string(word)
+                ));
+            }
+            offset += word.len();
+        }
+        writeln!(writer, "{{\"line\":{line_no},\"bad\":[{}]}}", bad_words.join(","))?;
+        writer.flush()
+    }
+
+    /// Checks one line of text and writes each misspelled word with its position,
+    /// e.g. "notes.txt:2:5: Fibula", as grep does. The column is counted in characters from 1.
+    pub fn check_text_positions(
+        &self,
+        text_parser: &TextParser,
+        text_name: &str,
+        line_no: usize,
+        untokenized: &str,
+        writer: &mut dyn Write,
+    ) -> io::Result<()> {
+        Self::write_checked_positions(text_name, line_no, &self.check_text_tokens(text_parser, untokenized), writer)
+    }
+
+    /// Writes the tokens checked by check_text_tokens, see check_text_positions.
+    fn write_checked_positions(
+        text_name: &str,
+        line_no: usize,
+        checked_tokens: &[(String, TokenType, Option<&str>)],
+        writer: &mut dyn Write,
+    ) -> io::Result<()> {
+        let mut column: usize = 1;
+        for (word, token_type, _lang_code) in checked_tokens {
+            if *token_type == TokenType::IsBadWord {
+                writeln!(writer, "{text_name}:{line_no}:{column}: {word}")?;
+            }
+            column += word.chars().count();
         }
-        //
         Ok(())
     }
 
+    /// The file name of the standard input, for the text or for the dictionary.
+    const STDIN_NAME: &'static str = "-";
+
+    /// Opens the text file for reading. The file name "-" is the standard input.
+    fn open_text_file(text_name: &str) -> io::Result<Box<dyn BufRead>> {
+        if text_name == Self::STDIN_NAME {
+            Ok(Box::new(io::stdin().lock()))
+        } else {
+            Ok(Box::new(BufReader::new(File::open(text_name)?)))
+        }
+    }
+
+    /// Checks the text file line by line. The file name "-" is the standard input.
+    /// With option --summary-per-file, returns the misspelled words of the file,
+    /// counted from the same checked tokens as the output.
+    pub fn check_text_file(&self, text_parser: &mut TextParser, text_name: &String) -> io::Result<FileSummary> {
+        let mut file_summary = FileSummary::new(text_name);
+        let reader = Self::open_text_file(text_name)?;
+        for (line_ix, line) in reader.lines().enumerate() {
+            let untokenized = line?;
+            let checked_tokens = self.check_text_tokens(text_parser, &untokenized);
+            if self.csr_jsonl {
+                Self::write_checked_jsonl(line_ix + 1, &checked_tokens, &mut stdout())?;
+            } else if self.csr_positions {
+                Self::write_checked_positions(text_name, line_ix + 1, &checked_tokens, &mut stdout())?;
+            } else {
+                self.write_checked_text(text_parser, &checked_tokens, &mut stdout())?;
+            }
+            if self.csr_summary_per_file {
+                file_summary.add_tokens(&checked_tokens);
+            }
+        }
+        Ok(file_summary)
+    }
+
+    /// Returns true if the word matches the filters of option --test-word. The filters
+    /// can have the wildcard '*', e.g. "pre*". The filters starting with '!' exclude words.
+    /// Without other filters than the excluding ones, all the other words are selected.
+    pub fn test_word_selected(test_words: &[&str], word: &str) -> bool {
+        let mut any_including = false;
+        let mut included = false;
+        for filter in test_words {
+            if let Some(excluding) = filter.strip_prefix('!') {
+                if Self::wildcard_match(excluding, word) {
+                    return false;
+                }
+            } else {
+                any_including = true;
+                included = included || Self::wildcard_match(filter, word);
+            }
+        }
+        included || !any_including
+    }
+
+    /// Returns true if the text matches the pattern, where '*' matches any characters.
+    pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+        let mut parts = pattern.split('*');
+        let first = parts.next().unwrap_or("");
+        let Some(mut rest) = text.strip_prefix(first) else {
+            return false;
+        };
+        let parts: Vec<&str> = parts.collect();
+        let Some((last, middle)) = parts.split_last() else {
+            return rest.is_empty(); // no wildcard
+        };
+        for part in middle {
+            match rest.find(part) {
+                Some(part_ix) => rest = &rest[part_ix + part.len()..],
+                None => return false,
+            }
+        }
+        rest.ends_with(last)
+    }
+
     /// Runs a test case, either all words or a selection of words
     /// 'base_file_name' is nearly full file name, it's only missing file extension.
     /// 'test_case_name' is derived from 'base_file_name' and has no file separators.
@@ -603,7 +1226,7 @@ impl CliSpeller {
                 if word.len() == 0 {
                     continue;
                 }
-                if test_words.len() != 0 && !test_words.contains(&word.as_str()) {
+                if !Self::test_word_selected(test_words, &word) {
                     continue;
                 }
                 let test_passed = if sec_ix == 0 {
@@ -662,9 +1285,22 @@ impl CliSpeller {
 
     pub fn execute_task(&mut self, text_parser: &mut TextParser) {
         if let Ok(_) = self.open_out_file(text_parser) {
+            if let Some((base_code, new_code)) = self.csr_diff.clone() {
+                // the text files are the word list, they're not checked
+                self.diff_dictionaries(text_parser, &base_code, &new_code);
+                return;
+            }
             let dict_code_string = self.csr_dict_codes.clone();
             for dict_code_ext in dict_code_string.split(",") {
                 self.read_lang_ext(text_parser, dict_code_ext);
+                if let Some(list_flag) = &self.csr_list_flag {
+                    if let Some(spell_lang) = text_parser.tps_langs.last() {
+                        self.list_flagged(spell_lang, list_flag);
+                    }
+                }
+                if let Some(spell_lang) = text_parser.tps_langs.last() {
+                    self.test_flags(spell_lang);
+                }
                 if self.csr_text_files.is_empty() {
                     // only parsing was interesting, now the language can be removed
                     let _lang = text_parser.tps_langs.pop();
@@ -686,19 +1322,261 @@ impl CliSpeller {
                 }
                 self.run_test_ext(text_parser, &ext_code_vec, &test_words);
             }
+            if self.csr_unused_affixes {
+                // the text files are the corpus, they're not checked
+                let mut texts: Vec<String> = vec![];
+                for text_name in &self.csr_text_files {
+                    let mut text = String::new();
+                    match Self::open_text_file(text_name).and_then(|mut reader| reader.read_to_string(&mut text)) {
+                        Ok(_) => texts.push(text),
+                        Err(_) => println!("Could not read: {text_name}"),
+                    }
+                }
+                if let Some(spell_lang) = text_parser.tps_langs.last() {
+                    let _ = self.write_unused_affixes(spell_lang, &texts, &mut stdout());
+                }
+                return;
+            }
+            let mut file_summaries: Vec<FileSummary> = vec![];
             for text_name in &self.csr_text_files {
-                let _ = self.check_text_file(text_parser, &text_name);
+                if let Ok(file_summary) = self.check_text_file(text_parser, text_name) {
+                    file_summaries.push(file_summary);
+                }
+            }
+            if self.csr_summary_per_file {
+                for file_summary in &file_summaries {
+                    let _ = file_summary.write(&mut stdout());
+                }
             }
         } else {
             println!("Could not start");
         }
     }
 
-    pub fn do_all(&mut self, args: Vec<String>) {
+    /// Returns false when the exit status should be failure, see option --strict.
+    pub fn do_all(&mut self, args: Vec<String>) -> bool {
         let mut text_parser = TextParser::new();
         self.csr_arg_tokens.set_arguments(args);
         self.process_environment_variables();
         self.parse_cli_options(&mut text_parser);
+        if text_parser.tps_showing_details {
+            for note in &self.spl_path_notes {
+                text_parser.store_note(note);
+            }
+        }
         self.execute_task(&mut text_parser);
+        !text_parser.tps_strict_failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CliSpeller, DictRegistry};
+    use neaspell_core::core_speller::{Spell, SpellLang};
+    use neaspell_core::text_parser::TextParser;
+    use std::io::Cursor;
+    use std::path::{Path, MAIN_SEPARATOR};
+
+    /// Writes the files to a new temporary directory, runs the test in it and removes the directory.
+    fn with_temp_files<T>(dir_name: &str, files: &[(&str, &str)], test: impl FnOnce(&Path) -> T) -> T {
+        let dir = std::env::temp_dir().join(format!("{dir_name}_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file_name, content) in files {
+            std::fs::write(dir.join(file_name), content).unwrap();
+        }
+        let result = test(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        result
+    }
+
+    #[test]
+    fn check_text_jsonl_test() {
+        let spell_lang = SpellLang::new("test");
+        let mut text_parser = TextParser::new();
+        text_parser.tps_langs.push(spell_lang);
+        let cli_speller = CliSpeller::new();
+        let mut output: Vec<u8> = vec![];
+        cli_speller.check_text_jsonl(&text_parser, 1, "", &mut output).unwrap();
+        cli_speller.check_text_jsonl(&text_parser, 2, "a \"quoted\" word", &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "{\"line\":1,\"bad\":[]}");
+        assert!(lines[1].starts_with("{\"line\":2,\"bad\":[{\"word\":\"a\",\"offset\":0},"));
+        assert!(lines[1].contains("{\"word\":\"quoted\",\"offset\":3}"));
+        assert_eq!(CliSpeller::json_string("\"\\"), "\"\\\"\\\\\"");
+    }
+
+    #[test]
+    fn check_text_positions_test() {
+        let mut text_parser = TextParser::new();
+        let mut cli_speller = CliSpeller::new();
+        let neadic = "NEA DIC {\n    the\n    cat\n}\n";
+        cli_speller.read_lang_reader(&mut text_parser, "test", Box::new(Cursor::new(neadic)));
+        let mut output: Vec<u8> = vec![];
+        cli_speller.check_text_positions(&text_parser, "a.txt", 1, "the cat", &mut output).unwrap();
+        cli_speller.check_text_positions(&text_parser, "a.txt", 2, "the\u{e9} cat dgo", &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, "a.txt:2:1: the\u{e9}\na.txt:2:10: dgo\n");
+    }
+
+    #[test]
+    fn check_text_suggestions_test() {
+        let mut text_parser = TextParser::new();
+        let mut cli_speller = CliSpeller::new();
+        let neadic = "MAXNGRAMSUGS 0\nNEA DIC {\n    the\n    cat\n    dog\n}\n";
+        cli_speller.read_lang_reader(&mut text_parser, "test", Box::new(Cursor::new(neadic)));
+        text_parser.tps_check_level = 2;
+        let mut output: Vec<u8> = vec![];
+        cli_speller.check_text(&text_parser, "the\u{e9} cat dgo xyzzy", &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, "& the\u{e9} 1 0: the\n*\n& dgo 1 10: dog\n# xyzzy 14\n");
+    }
+
+    #[test]
+    fn max_suggestions_option_test() {
+        let mut text_parser = TextParser::new();
+        let mut cli_speller = CliSpeller::new();
+        cli_speller.csr_arg_tokens.set_arguments(["neaspell", "--suggest-max", "3"].map(String::from).to_vec());
+        cli_speller.parse_cli_options(&mut text_parser);
+        assert_eq!(text_parser.tps_suggest_max, 3);
+        // not a number, the value is kept as with --max-notes
+        let args = ["neaspell", "--suggest-max", "x", "--max-notes", "y"];
+        cli_speller.csr_arg_tokens.set_arguments(args.map(String::from).to_vec());
+        cli_speller.parse_cli_options(&mut text_parser);
+        assert_eq!(text_parser.tps_suggest_max, 3);
+        assert_eq!(text_parser.tps_max_notes, 10);
+        cli_speller.csr_arg_tokens.set_arguments(["neaspell", "--max-suggestions", "1"].map(String::from).to_vec());
+        cli_speller.parse_cli_options(&mut text_parser);
+        assert_eq!(text_parser.tps_suggest_max, 1);
+        let neadic = "NEA DIC {\n    cat\n    cot\n}\n";
+        cli_speller.read_lang_reader(&mut text_parser, "test", Box::new(Cursor::new(neadic)));
+        cli_speller.read_lang_reader(&mut text_parser, "test2", Box::new(Cursor::new(neadic)));
+        text_parser.tps_check_level = 2;
+        let mut output: Vec<u8> = vec![];
+        cli_speller.check_text(&text_parser, "cxt", &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, "& cxt 1 0: cat\n");
+    }
+
+    #[test]
+    fn features_option_test() {
+        let neadic = "COMPOUNDFLAG Y\nNEA DIC {\n    foot/Y\n    ball/Y\n}\n";
+        let mut text_parser = TextParser::new();
+        let mut cli_speller = CliSpeller::new();
+        cli_speller.read_lang_reader(&mut text_parser, "test", Box::new(Cursor::new(neadic)));
+        cli_speller.csr_arg_tokens.set_arguments(["neaspell", "--features", "-compound,+ngram"].map(String::from).to_vec());
+        cli_speller.parse_cli_options(&mut text_parser);
+        cli_speller.read_lang_reader(&mut text_parser, "test2", Box::new(Cursor::new(neadic)));
+        assert!(Spell::check_token(&text_parser.tps_langs[0], "football"));
+        assert!(!Spell::check_token(&text_parser.tps_langs[1], "football"));
+        assert!(Spell::check_token(&text_parser.tps_langs[1], "ball"));
+    }
+
+    #[test]
+    fn supplement_without_language_test() {
+        let mut text_parser = TextParser::new();
+        let mut cli_speller = CliSpeller::new();
+        cli_speller.read_lang_supplement(&mut text_parser, "missing_med".to_string());
+        assert!(text_parser.tps_langs.is_empty());
+    }
+
+    #[test]
+    fn write_unused_affixes_test() {
+        let neadic = "SFX S Y 1\nSFX S 0 s .\nSFX D Y 1\nSFX D 0 ed .\nPFX U Y 1\nPFX U 0 un .\nNEA DIC {\n    walk/SDU\n}\n";
+        let mut text_parser = TextParser::new();
+        let mut cli_speller = CliSpeller::new();
+        cli_speller.read_lang_reader(&mut text_parser, "stdin", Box::new(Cursor::new(neadic)));
+        let spell_lang = text_parser.tps_langs.last().unwrap();
+        let mut output: Vec<u8> = vec![];
+        let texts = vec!["walks walk".to_string(), "walked walkz".to_string()];
+        cli_speller.write_unused_affixes(spell_lang, &texts, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "U\n");
+    }
+
+    #[test]
+    fn file_summary_test() {
+        let neadic = "NEA DIC {\n    the\n    cat\n}\n";
+        let mut text_parser = TextParser::new();
+        let mut cli_speller = CliSpeller::new();
+        cli_speller.read_lang_reader(&mut text_parser, "stdin", Box::new(Cursor::new(neadic)));
+        cli_speller.csr_summary_per_file = true;
+        text_parser.tps_skip_output = true;
+        let files = [("a.txt", "teh cat\nthe dgo teh\n"), ("b.txt", "the cat\n")];
+        let (file1, file2, summary1, summary2) = with_temp_files("neaspell_summary", &files, |dir| {
+            let file1 = dir.join("a.txt").to_string_lossy().to_string();
+            let file2 = dir.join("b.txt").to_string_lossy().to_string();
+            let summary1 = cli_speller.check_text_file(&mut text_parser, &file1).unwrap();
+            let summary2 = cli_speller.check_text_file(&mut text_parser, &file2).unwrap();
+            (file1, file2, summary1, summary2)
+        });
+        assert_eq!(summary1.fsm_bad_count, 3);
+        assert_eq!(summary1.top_words(5), vec![("teh", 2), ("dgo", 1)]);
+        assert_eq!(summary2.fsm_bad_count, 0);
+        let mut output: Vec<u8> = vec![];
+        summary1.write(&mut output).unwrap();
+        summary2.write(&mut output).unwrap();
+        let expected = format!("{file1}: 3 misspelled: teh (2), dgo (1)\n{file2}: 0 misspelled\n");
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn dict_registry_test() {
+        let files = [
+            ("en_US.aff", "SET UTF-8\nSFX S Y 1\nSFX S 0 s .\n"),
+            ("en_US.dic", "1\ncat/S\n"),
+            ("de.aff", "SET UTF-8\n"),
+            ("de.dic", "1\nKatze\n"),
+            ("fr.aff", "SET UTF-8\n"), // without .dic, not loaded
+        ];
+        let registry = with_temp_files("neaspell_registry", &files, |dir| DictRegistry::load_dir(&dir.to_string_lossy()));
+        assert_eq!(registry.codes(), vec!["de", "en_US"]);
+        let en_lang = registry.get("en_US").unwrap();
+        assert!(Spell::check_token(en_lang, "cats"));
+        assert!(!Spell::check_token(en_lang, "Katze"));
+        assert!(Spell::check_token(registry.get("de").unwrap(), "Katze"));
+        assert!(registry.get("fr").is_none());
+    }
+
+    #[test]
+    fn test_word_selected_test() {
+        let words = ["prefix", "preview", "review", "viewer"];
+        let selected = |filters: &[&str]| -> Vec<&str> {
+            words.iter().copied().filter(|word| CliSpeller::test_word_selected(filters, word)).collect()
+        };
+        assert_eq!(selected(&[]), words.to_vec());
+        assert_eq!(selected(&["review"]), vec!["review"]);
+        assert_eq!(selected(&["pre*"]), vec!["prefix", "preview"]);
+        assert_eq!(selected(&["pre*", "!*view"]), vec!["prefix"]);
+        assert_eq!(selected(&["!pre*"]), vec!["review", "viewer"]);
+        assert_eq!(selected(&["*view*", "!re*"]), vec!["preview", "viewer"]);
+        assert!(CliSpeller::wildcard_match("a*b*a", "abba"));
+        assert!(!CliSpeller::wildcard_match("a*b*a", "aba*"));
+        assert!(!CliSpeller::wildcard_match("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn read_lang_reader_test() {
+        let neadic = "SFX S Y 1\nSFX S 0 s .\nNEA DIC {\n    cat/S\n}\n";
+        let mut text_parser = TextParser::new();
+        let mut cli_speller = CliSpeller::new();
+        cli_speller.read_lang_reader(&mut text_parser, "stdin", Box::new(Cursor::new(neadic)));
+        let spell_lang = text_parser.tps_langs.last().unwrap();
+        assert_eq!(spell_lang.slg_code, "stdin");
+        assert!(Spell::check_token(spell_lang, "cats"));
+        assert!(!Spell::check_token(spell_lang, "dogs"));
+    }
+
+    #[test]
+    fn check_search_dir_test() {
+        assert_eq!(CliSpeller::check_search_dir("NEA_DICPATH", "."), None);
+        assert_eq!(
+            CliSpeller::check_search_dir("NEA_DICPATH", &format!("no_such_dir{MAIN_SEPARATOR}*")),
+            Some(String::from("NEA_DICPATH: Directory not found: no_such_dir"))
+        );
+        assert_eq!(
+            CliSpeller::check_search_dir("NEA_TESTPATH", "Cargo.toml"),
+            Some(String::from("NEA_TESTPATH: Not a directory: Cargo.toml"))
+        );
     }
 }
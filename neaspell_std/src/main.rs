@@ -1,10 +1,15 @@
 // The main function that calls the library to do all.
 use neaspell_std::CliSpeller;
 use std::env;
+use std::process::ExitCode;
 
-fn main() {
+fn main() -> ExitCode {
     let mut cli_speller = CliSpeller::new();
-    cli_speller.do_all(env::args().collect());
+    if cli_speller.do_all(env::args().collect()) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
 }
 /*
 cd C:\0prog\spelling\neaspell
@@ -53,22 +53,10 @@ impl WorkSet {
     }
 
     pub fn load_language (&mut self, base_name: &str, aff_text:Vec<String>, dic_text:Vec<String>) -> Vec<String> {
-        //let mut ws_spell_lang = SpellLang::new(base_name);
-        let mut notes: Vec<String> = vec![];
-        // aff file
-        let mut aff_line_reader = WasmLineReader::new(base_name, TextParser::EXT_AFF, aff_text);
-        self.ws_text_parser.parse_dictionary_text(&mut self.ws_spell_lang, &mut aff_line_reader);
-        for line_note in &self.ws_text_parser.tps_line_notes {
-            notes.push (line_note.clone());
-        }
-        self.ws_text_parser.tps_line_notes.clear();
-        // dic file
-        let mut dic_line_reader = WasmLineReader::new(base_name, TextParser::EXT_DIC, dic_text);
-        self.ws_text_parser.parse_dictionary_text(&mut self.ws_spell_lang, &mut dic_line_reader);
-        for line_note in &self.ws_text_parser.tps_line_notes {
-            notes.push (line_note.clone());
-        }
-        notes
+        let aff_line_reader = WasmLineReader::new(base_name, TextParser::EXT_AFF, aff_text);
+        let dic_line_reader = WasmLineReader::new(base_name, TextParser::EXT_DIC, dic_text);
+        self.ws_spell_lang = self.ws_text_parser.parse_lang_readers(aff_line_reader, dic_line_reader);
+        self.ws_text_parser.tps_line_notes.drain(..).collect()
     }
 
     pub fn spell_text (&mut self, text:String) -> Vec<(String, TokenType)> {